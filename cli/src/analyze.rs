@@ -4,8 +4,16 @@ pub(crate) async fn analyze(site: Target) {
     match site {
         Target::Xing => todo!(),
         Target::Linkedin => todo!(),
-        Target::Stepstone => todo!(),
-        Target::Glassdoor => todo!(),
+        Target::Stepstone
+        | Target::Glassdoor
+        | Target::Indeed
+        | Target::Jsonld
+        | Target::Greenhouse
+        | Target::Lever
+        | Target::Personio
+        | Target::Arbeitsagentur => {
+            log::warn!("Analysis of {} postings is not supported yet", site.name());
+        }
         Target::Instaffo => todo!(),
     }
 }
@@ -66,8 +66,16 @@ pub async fn fix(site: Target) {
                 .await;
             log::info!("Query result: {:?}", delete_result);
         }
-        Target::Stepstone => todo!(),
-        Target::Glassdoor => todo!(),
+        Target::Stepstone
+        | Target::Glassdoor
+        | Target::Indeed
+        | Target::Jsonld
+        | Target::Greenhouse
+        | Target::Lever
+        | Target::Personio
+        | Target::Arbeitsagentur => {
+            log::warn!("Fixing {} postings is not supported yet", site.name());
+        }
         Target::Instaffo => todo!(),
     }
 }
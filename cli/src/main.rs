@@ -1,10 +1,12 @@
 mod analyze;
 mod fix;
+mod runs;
 mod scrape;
 
 use clap::{Parser, Subcommand};
 use dotenv::dotenv;
 use futures::{stream, StreamExt};
+use job_scraper::query::Recency;
 
 #[derive(Clone)]
 pub enum Target {
@@ -12,7 +14,31 @@ pub enum Target {
     Linkedin,
     Stepstone,
     Glassdoor,
+    Indeed,
     Instaffo,
+    Jsonld,
+    Greenhouse,
+    Lever,
+    Personio,
+    Arbeitsagentur,
+}
+
+impl Target {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Target::Xing => "xing",
+            Target::Linkedin => "linkedin",
+            Target::Stepstone => "stepstone",
+            Target::Glassdoor => "glassdoor",
+            Target::Indeed => "indeed",
+            Target::Instaffo => "instaffo",
+            Target::Jsonld => "jsonld",
+            Target::Greenhouse => "greenhouse",
+            Target::Lever => "lever",
+            Target::Personio => "personio",
+            Target::Arbeitsagentur => "arbeitsagentur",
+        }
+    }
 }
 
 impl From<String> for Target {
@@ -22,7 +48,13 @@ impl From<String> for Target {
             "linkedin" => Target::Linkedin,
             "stepstone" => Target::Stepstone,
             "glassdoor" => Target::Glassdoor,
+            "indeed" => Target::Indeed,
             "instaffo" => Target::Instaffo,
+            "jsonld" => Target::Jsonld,
+            "greenhouse" => Target::Greenhouse,
+            "lever" => Target::Lever,
+            "personio" => Target::Personio,
+            "arbeitsagentur" => Target::Arbeitsagentur,
             _ => panic!("Unknown target: {}", s),
         }
     }
@@ -41,9 +73,25 @@ struct Cli {
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    Scrape {},
+    Scrape {
+        /// Only include postings published within this window: day (24h), week, month or any
+        #[clap(long)]
+        recency: Option<Recency>,
+        /// Locations to search in, every query is searched once per location
+        #[clap(long, default_value = "Germany")]
+        location: Vec<String>,
+        /// Continue the searches of an interrupted run from their checkpoints
+        #[clap(long)]
+        resume: bool,
+    },
     Analyze {},
     Fix {},
+    /// Lists the last scrapes of the given sites with their statistics
+    Runs {
+        /// Number of runs listed per site
+        #[clap(long, default_value_t = 20)]
+        limit: i64,
+    },
 }
 
 #[tokio::main]
@@ -51,10 +99,47 @@ async fn main() {
     dotenv().ok();
     env_logger::init();
     let args = Cli::parse();
-    let sites = args.site.into_iter().map(Target::from);
     match args.command {
-        Commands::Scrape {} => stream::iter(sites).for_each(scrape::scrape).await,
-        Commands::Analyze {} => stream::iter(sites).for_each(analyze::analyze).await,
-        Commands::Fix {} => stream::iter(sites).for_each(fix::fix).await,
+        Commands::Scrape {
+            recency,
+            location,
+            resume,
+        } => {
+            let params = scrape::search_params(recency, location);
+            stream::iter(args.site)
+                .for_each(|site| scrape::scrape(site, params.clone(), resume))
+                .await;
+            scrape::log_proxy_stats();
+            scrape::log_robots_stats();
+        }
+        Commands::Analyze {} => {
+            let sites = args.site.into_iter().map(Target::from);
+            stream::iter(sites).for_each(analyze::analyze).await
+        }
+        Commands::Fix {} => {
+            let sites = args.site.into_iter().map(Target::from);
+            stream::iter(sites).for_each(fix::fix).await
+        }
+        Commands::Runs { limit } => runs::runs(args.site, limit).await,
     };
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_every_source_is_a_target() {
+        for source in job_scraper::source::registry() {
+            assert_eq!(Target::from(source.name().to_owned()).name(), source.name());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_unsupported_site() {
+        analyze::analyze(Target::from("Indeed".to_owned())).await;
+        analyze::analyze(Target::Arbeitsagentur).await;
+        analyze::analyze(Target::Stepstone).await;
+        analyze::analyze(Target::Glassdoor).await;
+    }
+}
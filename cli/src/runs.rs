@@ -0,0 +1,76 @@
+use persistence::ScrapeRun;
+
+/// Prints the last runs of every given site, of all sites if none is given
+pub async fn runs(sites: Vec<String>, limit: i64) {
+    let mongodb_connection_url =
+        std::env::var("MONGODB_CONNECTION_URL").expect("MONGODB_CONNECTION_URL not set");
+    let database_name = std::env::var("DATABASE").expect("DATABASE not set");
+    let db = persistence::connect(&mongodb_connection_url, &database_name).await;
+    let sites = if sites.is_empty() {
+        vec![None]
+    } else {
+        sites.into_iter().map(Some).collect()
+    };
+    for site in sites {
+        match persistence::recent_runs(&db, site.as_deref(), limit).await {
+            Ok(runs) if runs.is_empty() => {
+                println!("No runs of {}", site.as_deref().unwrap_or("any site"))
+            }
+            Ok(runs) => runs.iter().for_each(|run| println!("{}", summary(run))),
+            Err(e) => log::error!("Failed to load the runs: {}", e),
+        }
+    }
+}
+
+/// A run on a single line, http errors are listed as `count x status`
+pub(crate) fn summary(run: &ScrapeRun) -> String {
+    let seconds = (run.finished_at - run.started_at).num_seconds();
+    let http_errors = if run.http_errors.is_empty() {
+        "none".to_owned()
+    } else {
+        run.http_errors
+            .iter()
+            .map(|(status, count)| format!("{} x {}", count, status))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "{} {} in {}m {}s, {} queries: {} pages, {} postings, {} new, {} duplicates, {} extraction failures, http errors: {}",
+        run.started_at.format("%Y-%m-%d %H:%M"),
+        run.site,
+        seconds / 60,
+        seconds % 60,
+        run.queries.len(),
+        run.pages,
+        run.postings,
+        run.inserted,
+        run.duplicates,
+        run.extraction_failures,
+        http_errors
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use job_scraper::stats::Counts;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_summary() {
+        let mut run = ScrapeRun::start("xing", vec!["Rust in Germany".to_owned()]);
+        run.postings = 80;
+        run.inserted = 20;
+        run.duplicates = 60;
+        run.finish(Counts {
+            pages: 120,
+            http_errors: BTreeMap::from([(404, 1), (429, 3)]),
+            extraction_failures: 2,
+        });
+        assert!(run.started_at <= run.finished_at);
+        assert!(summary(&run).ends_with(
+            "xing in 0m 0s, 1 queries: 120 pages, 80 postings, 20 new, 60 duplicates, \
+             2 extraction failures, http errors: 1 x 404, 3 x 429"
+        ));
+    }
+}
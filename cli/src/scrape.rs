@@ -1,76 +1,184 @@
-use crate::Target;
-use futures::{
-    stream::{self, BufferUnordered, Chunks},
-    Future, Stream, StreamExt,
-};
+use std::sync::Arc;
+
+use futures::{Stream, StreamExt};
+use job_scraper::checkpoint::Checkpoints;
+use job_scraper::query::{Recency, SearchQuery};
+use job_scraper::source::{JobSource, SearchParams};
 use job_scraper::Job;
-use persistence::{save_many, ScrapedJob};
-use tokio::fs::File;
+use persistence::save_many;
+
+/// Postings of a run and what became of them on insert
+#[derive(Debug, Default)]
+struct Inserts {
+    postings: u32,
+    inserted: u32,
+    duplicates: u32,
+}
 
 async fn save_job_stream(
-    stream: Chunks<impl Stream<Item = Job>>,
+    stream: impl Stream<Item = Vec<Job>>,
     collection: mongodb::Collection<persistence::ScrapedJob>,
-) {
+) -> Inserts {
+    let mut inserts = Inserts::default();
     tokio::pin!(stream);
     while let Some(result_chunk) = stream.next().await {
+        let postings = result_chunk.len() as u32;
+        inserts.postings += postings;
         let scraped_jobs = result_chunk
             .into_iter()
             .map(|job| persistence::ScrapedJob::new(job));
         match save_many(&collection, scraped_jobs).await {
             Ok(insert_result) => {
-                log::info!("Inserted {} jobs", insert_result.inserted_ids.len())
+                log::info!("Inserted {} jobs", insert_result.inserted_ids.len());
+                inserts.inserted += insert_result.inserted_ids.len() as u32;
             }
-            Err(e) => log::error!("Error inserting scraped jobs: {}", e),
+            Err(e) => match persistence::duplicates(&e) {
+                Some(duplicates) => {
+                    log::info!(
+                        "Inserted {} jobs, {} were stored before",
+                        postings - duplicates,
+                        duplicates
+                    );
+                    inserts.inserted += postings - duplicates;
+                    inserts.duplicates += duplicates;
+                }
+                None => log::error!("Error inserting scraped jobs: {}", e),
+            },
         }
     }
+    inserts
+}
+
+/// Builds the search for every combination of the default queries and the given locations
+pub(crate) fn search_params(recency: Option<Recency>, locations: Vec<String>) -> SearchParams {
+    let queries = DEFAULT_SEARCH_QUERIES
+        .into_iter()
+        .flat_map(|keywords| {
+            locations.iter().map(move |location| SearchQuery {
+                location: Some(location.to_owned()),
+                recency,
+                ..SearchQuery::new(keywords)
+            })
+        })
+        .collect();
+    SearchParams {
+        queries,
+        ..SearchParams::default()
+    }
 }
 
-pub(crate) async fn scrape(site: Target) {
+/// Scrapes the site into `scraped-jobs`, with `resume` the searches continue
+/// from the checkpoints of the last run
+pub(crate) async fn scrape(site: String, params: SearchParams, resume: bool) {
+    let source = match job_scraper::source::find(&site) {
+        Some(source) => source,
+        None => {
+            log::error!("Unknown site: {}", site);
+            return;
+        }
+    };
     let mongodb_connection_url =
         std::env::var("MONGODB_CONNECTION_URL").expect("MONGODB_CONNECTION_URL not set");
     let database_name = std::env::var("DATABASE").expect("DATABASE not set");
     let db = persistence::connect(&mongodb_connection_url, &database_name).await;
     let collection = db.collection::<persistence::ScrapedJob>("scraped-jobs");
-    match site {
-        Target::Xing => {
-            let queries = DEFAULT_SEARCH_QUERIES
-                .into_iter()
-                .map(String::from)
-                .collect();
-            let results = job_scraper::xing::scraper::scrape_queries(queries)
-                .await
-                .buffer_unordered(500)
-                .chunks(500);
-            save_job_stream(results, collection).await;
-        }
-        Target::Instaffo => {
-            println!("Please enter your session cookie:");
-            let mut session_cookie = String::new();
-            std::io::stdin()
-                .read_line(&mut session_cookie)
-                .expect("Failed to read line");
-            let session_cookie = session_cookie.trim();
-            let results = job_scraper::instaffo::scrape(session_cookie.into())
-                .await
-                .chunks(20);
-            save_job_stream(results, collection).await;
-        }
-        Target::Linkedin => {
-            let queries = DEFAULT_SEARCH_QUERIES
-                .into_iter()
-                .map(String::from)
-                .collect();
-            let locations = vec!["Germany".to_owned()];
-            let results = job_scraper::linkedin::scrape(queries, locations)
-                .await
-                .filter_map(|job| async { job })
-                .chunks(5);
-            save_job_stream(results, collection).await;
+    let known_ids = match persistence::known_site_ids(&collection, source.name()).await {
+        Ok(known_ids) => known_ids,
+        Err(e) => {
+            log::error!(
+                "Failed to load the stored postings, scraping all of them: {}",
+                e
+            );
+            Default::default()
         }
-        _ => {}
+    };
+    log::info!(
+        "Scraping {}, skipping {} stored postings",
+        source.name(),
+        known_ids.len()
+    );
+    let checkpoints = Checkpoints::new(Arc::new(persistence::MongoCheckpoints::new(&db)), resume);
+    let queries = params.queries.iter().map(describe).collect();
+    let mut run = persistence::ScrapeRun::start(source.name(), queries);
+    // responses of the previous site belong to its run
+    job_scraper::stats::shared().take();
+    let params = SearchParams {
+        known_ids,
+        checkpoints,
+        ..params
+    };
+    let results = job_chunks(source.as_ref(), params).await;
+    let inserts = save_job_stream(results, collection).await;
+    run.postings = inserts.postings;
+    run.inserted = inserts.inserted;
+    run.duplicates = inserts.duplicates;
+    run.finish(job_scraper::stats::shared().take());
+    log::info!("Finished {}", crate::runs::summary(&run));
+    if let Err(e) = persistence::save_run(&db, &run).await {
+        log::error!("Failed to save the run: {}", e);
     }
 }
 
+fn describe(query: &SearchQuery) -> String {
+    match &query.location {
+        Some(location) => format!("{} in {}", query.keywords, location),
+        None => query.keywords.clone(),
+    }
+}
+
+/// Logs how the proxies of `SCRAPER_PROXIES` performed over the run
+pub(crate) fn log_proxy_stats() {
+    let Some(pool) = job_scraper::proxy::shared() else {
+        return;
+    };
+    for stats in pool.stats() {
+        log::info!(
+            "Proxy {}: {:.1}% of {} requests succeeded, {} rate limited, {} failed{}",
+            stats.proxy,
+            stats.success_rate() * 100.0,
+            stats.requests(),
+            stats.rate_limited,
+            stats.failures,
+            if stats.healthy {
+                ""
+            } else {
+                ", taken out of rotation"
+            }
+        );
+    }
+}
+
+/// Logs how many requests were skipped per host because robots.txt disallowed them
+pub(crate) fn log_robots_stats() {
+    let Some(robots) = job_scraper::robots::shared() else {
+        return;
+    };
+    for (host, skipped) in robots.skipped() {
+        log::info!(
+            "Skipped {} requests to {} disallowed by robots.txt",
+            skipped,
+            host
+        );
+    }
+}
+
+/// Scraped jobs in chunks for bulk inserts, failed postings are logged and skipped
+async fn job_chunks(source: &dyn JobSource, params: SearchParams) -> impl Stream<Item = Vec<Job>> {
+    source
+        .jobs(params)
+        .await
+        .filter_map(|result| async move {
+            match result {
+                Ok(job) => Some(job),
+                Err(e) => {
+                    log::error!("Error scraping job: {}", e);
+                    None
+                }
+            }
+        })
+        .ready_chunks(100)
+}
+
 const DEFAULT_SEARCH_QUERIES: [&str; 27] = [
     "Swift",
     "React native",
@@ -83,8 +191,8 @@ const DEFAULT_SEARCH_QUERIES: [&str; 27] = [
     "Go Programming Language",
     "Elixir",
     "Kotlin",
-    "C%2B%2B",
-    "C%23",
+    "C++",
+    "C#",
     "Dotnet",
     "Spring Boot",
     "Microservices",
@@ -100,3 +208,175 @@ const DEFAULT_SEARCH_QUERIES: [&str; 27] = [
     "NextJS",
     "Python",
 ];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use job_scraper::greenhouse::GreenhouseSource;
+    use job_scraper::instaffo::InstaffoSource;
+    use job_scraper::lever::LeverSource;
+    use job_scraper::linkedin::LinkedinSource;
+    use job_scraper::personio::PersonioSource;
+    use job_scraper::xing::scraper::XingSource;
+    use mock_server::{Config, MockServer};
+
+    fn params(keywords: &str) -> SearchParams {
+        SearchParams {
+            queries: vec![SearchQuery::new(keywords)],
+            ..SearchParams::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrape_xing_from_mock() {
+        let server = MockServer::start(Config {
+            jobs: 8,
+            rate_limit_every: Some(3),
+            malformed_every: Some(4),
+            ..Config::default()
+        })
+        .await;
+        let source = XingSource {
+            base_url: server.xing_url(),
+            ..Default::default()
+        };
+        let jobs = job_chunks(&source, params("Software Engineer"))
+            .await
+            .concat()
+            .await;
+        assert_eq!(jobs.len(), 8);
+        let without_content = jobs
+            .iter()
+            .filter(|job| matches!(job, Job::Xing { raw_data: None, .. }))
+            .count();
+        assert_eq!(without_content, 2);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_xing_malformed_search() {
+        let server = MockServer::start(Config::default()).await;
+        let source = XingSource {
+            base_url: server.xing_url(),
+            ..Default::default()
+        };
+        let jobs = job_chunks(&source, params("malformed"))
+            .await
+            .concat()
+            .await;
+        assert!(jobs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scrape_linkedin_from_mock() {
+        let server = MockServer::start(Config {
+            jobs: 6,
+            rate_limit_every: Some(4),
+            malformed_every: Some(3),
+            ..Config::default()
+        })
+        .await;
+        let source = LinkedinSource {
+            base_url: server.linkedin_url(),
+            ..Default::default()
+        };
+        let jobs = job_chunks(&source, params("Rust")).await.concat().await;
+        assert_eq!(jobs.len(), 6);
+        let with_title = jobs
+            .iter()
+            .filter(|job| matches!(job, Job::Linkedin { job } if job.title.is_some()))
+            .count();
+        assert_eq!(with_title, 4);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_skips_known_postings() {
+        let server = MockServer::start(Config {
+            jobs: 6,
+            ..Config::default()
+        })
+        .await;
+        let source = LinkedinSource {
+            base_url: server.linkedin_url(),
+            ..Default::default()
+        };
+        let first_run = job_chunks(&source, params("Rust")).await.concat().await;
+        assert_eq!(server.posting_requests(), 6);
+
+        // only the postings missing from the first run are requested
+        let known_ids = first_run.iter().take(4).map(Job::site_id).collect();
+        let params = SearchParams {
+            known_ids,
+            ..params("Rust")
+        };
+        let second_run = job_chunks(&source, params).await.concat().await;
+        assert_eq!(second_run.len(), 2);
+        assert_eq!(server.posting_requests(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_instaffo_from_mock() {
+        let server = MockServer::start(Config {
+            jobs: 45,
+            rate_limit_every: Some(2),
+            malformed_every: None,
+            ..Config::default()
+        })
+        .await;
+        let cookie_jar =
+            std::env::temp_dir().join(format!("cli-instaffo-{}.json", std::process::id()));
+        let source = InstaffoSource {
+            base_url: server.instaffo_url(),
+            cookie_jar: cookie_jar.clone(),
+            session: Some("mock-session".to_owned()),
+            credentials: None,
+            ..Default::default()
+        };
+        let jobs = job_chunks(&source, SearchParams::default())
+            .await
+            .concat()
+            .await;
+        assert_eq!(jobs.len(), 45);
+        // the renewed session is picked up by the next run
+        let cookies = job_scraper::cookies::CookieJar::open(&cookie_jar).unwrap();
+        assert_eq!(
+            cookies.get("_instaffo_session").as_deref(),
+            Some(mock_server::RENEWED_SESSION)
+        );
+        std::fs::remove_file(cookie_jar).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scrape_ats_boards_from_mock() {
+        let server = MockServer::start(Config {
+            jobs: 3,
+            ..Config::default()
+        })
+        .await;
+        let companies = vec!["acme".to_owned(), "missing".to_owned()];
+        let sources: Vec<Box<dyn JobSource>> = vec![
+            Box::new(GreenhouseSource {
+                boards: companies.clone(),
+                base_url: server.greenhouse_url(),
+            }),
+            Box::new(LeverSource {
+                companies: companies.clone(),
+                base_url: server.lever_url(),
+            }),
+            Box::new(PersonioSource {
+                companies,
+                base_url: server.personio_url(),
+            }),
+        ];
+        for source in sources {
+            let jobs = job_chunks(source.as_ref(), SearchParams::default())
+                .await
+                .concat()
+                .await;
+            assert_eq!(jobs.len(), 3, "{}", source.name());
+            for job in jobs {
+                let scraped = persistence::ScrapedJob::new(job);
+                assert!(mongodb::bson::to_document(&scraped).is_ok());
+            }
+        }
+    }
+}
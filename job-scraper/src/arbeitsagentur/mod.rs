@@ -0,0 +1,370 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::NaiveDate;
+use futures::{Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::client::Client;
+use crate::headers::{self, Destination};
+use crate::query::{url_with_params, EmploymentType, SearchQuery};
+use crate::source::{base_url, JobSource, JobStream, SearchParams};
+
+type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Request error: '{0}'")]
+    Request(#[from] reqwest::Error),
+    #[error("Failed to scrape data from: '{0}'")]
+    RequestNotOk(String),
+}
+
+/// Jobsuche api of the Bundesagentur für Arbeit, `ARBEITSAGENTUR_BASE_URL` points the scraper
+/// somewhere else
+pub const BASE_URL: &str = "https://rest.arbeitsagentur.de/jobboerse/jobsuche-service";
+/// Client id of the agency's own web app, the api rejects requests without it
+const API_KEY: &str = "jobboerse-jobsuche";
+/// Largest page the api serves
+const PAGE_SIZE: u32 = 100;
+/// The api doesn't page beyond 10000 results
+const MAX_PAGES: u32 = 100;
+
+#[derive(Deserialize, Default)]
+struct Place {
+    plz: Option<String>,
+    ort: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Listing {
+    refnr: String,
+    titel: Option<String>,
+    beruf: Option<String>,
+    arbeitgeber: Option<String>,
+    #[serde(default)]
+    arbeitsort: Place,
+    aktuelle_veroeffentlichungsdatum: Option<NaiveDate>,
+    eintrittsdatum: Option<NaiveDate>,
+    externe_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    stellenangebote: Vec<Listing>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Details {
+    #[serde(alias = "stellenbeschreibung")]
+    stellenangebots_beschreibung: Option<String>,
+    #[serde(default)]
+    arbeitszeitmodelle: Vec<String>,
+    verguetung: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Job {
+    /// Reference number of the agency, e.g. "10000-1186541843-S"
+    pub refnr: String,
+    pub title: Option<String>,
+    /// Occupation the agency filed the posting under
+    pub occupation: Option<String>,
+    pub employer: Option<String>,
+    pub location: Option<String>,
+    pub published_at: Option<NaiveDate>,
+    /// First day of work, if the employer named one
+    pub entry_date: Option<NaiveDate>,
+    pub link: String,
+    /// Posting on the employer's site, the agency only links to some postings
+    pub external_url: Option<String>,
+    /// e.g. "VOLLZEIT" or "HEIMARBEIT"
+    pub working_time: Vec<String>,
+    pub salary: Option<String>,
+}
+
+impl From<Listing> for Job {
+    fn from(listing: Listing) -> Self {
+        let location = [listing.arbeitsort.plz, listing.arbeitsort.ort]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        Job {
+            link: format!(
+                "https://www.arbeitsagentur.de/jobsuche/jobdetail/{}",
+                listing.refnr
+            ),
+            refnr: listing.refnr,
+            title: listing.titel,
+            occupation: listing.beruf,
+            employer: listing.arbeitgeber,
+            location: (!location.is_empty()).then(|| location.join(" ")),
+            published_at: listing.aktuelle_veroeffentlichungsdatum,
+            entry_date: listing.eintrittsdatum,
+            external_url: listing.externe_url,
+            working_time: Vec::new(),
+            salary: None,
+        }
+    }
+}
+
+fn job_search_url(base_url: &str, query: &SearchQuery, page: u32) -> String {
+    let mut params = vec![
+        ("was", query.keywords.clone()),
+        ("page", page.to_string()),
+        ("size", PAGE_SIZE.to_string()),
+    ];
+    if let Some(location) = &query.location {
+        params.push(("wo", location.clone()));
+    }
+    if let Some(radius) = query.radius {
+        params.push(("umkreis", radius.to_string()));
+    }
+    if let Some(days) = query.recency.and_then(|recency| recency.days()) {
+        params.push(("veroeffentlichtseit", days.to_string()));
+    }
+    let mut working_time = Vec::new();
+    match query.employment_type {
+        Some(EmploymentType::FullTime) => working_time.push("vz"),
+        Some(EmploymentType::PartTime) => working_time.push("tz"),
+        Some(EmploymentType::Freelance) => params.push(("angebotsart", "2".to_owned())),
+        Some(EmploymentType::Internship) => params.push(("angebotsart", "34".to_owned())),
+        Some(EmploymentType::Temporary) => params.push(("befristung", "1".to_owned())),
+        None => {}
+    }
+    if query.remote {
+        working_time.push("ho");
+    }
+    if !working_time.is_empty() {
+        params.push(("arbeitszeit", working_time.join(";")));
+    }
+    url_with_params(&format!("{}/pc/v4/jobs", base_url), params)
+}
+
+/// Details are requested by the base64 encoded reference number
+fn job_details_url(base_url: &str, refnr: &str) -> String {
+    let id = base64::engine::general_purpose::STANDARD.encode(refnr);
+    format!("{}/pc/v4/jobdetails/{}", base_url, urlencoding::encode(&id))
+}
+
+async fn scrape_search_page(
+    client: &Client,
+    base_url: &str,
+    query: &SearchQuery,
+    page: u32,
+) -> Result<Vec<Listing>> {
+    let url = job_search_url(base_url, query, page);
+    log::debug!(
+        "requesting jobs from arbeitsagentur, page: {}, search: {}",
+        page,
+        query.keywords
+    );
+    let resp = client
+        .get(&url)
+        .destination(Destination::Fetch)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        log::error!(
+            "failed to retrieve results for page: {}, search: {}, status: {}",
+            page,
+            query.keywords,
+            resp.status()
+        );
+        return Err(Error::RequestNotOk(url));
+    }
+    Ok(resp.json::<SearchResponse>().await?.stellenangebote)
+}
+
+async fn scrape_job_details(client: &Client, base_url: &str, refnr: &str) -> Result<Details> {
+    let url = job_details_url(base_url, refnr);
+    let resp = client
+        .get(&url)
+        .destination(Destination::Fetch)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(Error::RequestNotOk(url));
+    }
+    Ok(resp.json().await?)
+}
+
+/// fetch the description, working time and salary of the posting
+/// then convert it to a crate::Job
+async fn convert(client: &Client, base_url: &str, mut job: Job) -> crate::Job {
+    let raw_data = match scrape_job_details(client, base_url, &job.refnr).await {
+        Ok(details) => {
+            job.working_time = details.arbeitszeitmodelle;
+            job.salary = details.verguetung;
+            details.stellenangebots_beschreibung
+        }
+        Err(e) => {
+            log::error!(
+                "failed to scrape job details for refnr: {}, error: {}",
+                job.refnr,
+                e
+            );
+            client.extraction_failed();
+            None
+        }
+    };
+    crate::Job::Arbeitsagentur {
+        job: Box::new(job),
+        raw_data,
+    }
+}
+
+/// Headers of the agency's web app, the api key included
+fn api_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    headers.insert(
+        HeaderName::from_static("x-api-key"),
+        HeaderValue::from_static(API_KEY),
+    );
+    headers
+}
+
+/// Scrape all jobs for given queries with their details,
+/// postings in `known_ids` were stored before and are skipped
+pub async fn scrape(
+    base_url: String,
+    queries: Vec<SearchQuery>,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = crate::Job> {
+    let client = Client::builder(reqwest::Client::new())
+        .profile(headers::random())
+        .default_headers(api_headers())
+        .build();
+    scrape_with(client, base_url, queries, known_ids)
+}
+
+fn scrape_with(
+    client: Client,
+    base_url: String,
+    queries: Vec<SearchQuery>,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = crate::Job> {
+    let mut scraped_ids: HashSet<String> = known_ids;
+    async_stream::stream! {
+        for query in queries {
+            for page in 1..=MAX_PAGES {
+                let listings = match scrape_search_page(&client, &base_url, &query, page).await {
+                    Ok(listings) => listings,
+                    Err(e) => {
+                        log::error!("Stopping search for query: {}, error: {}", query.keywords, e);
+                        break;
+                    }
+                };
+                let count = listings.len() as u32;
+                for listing in listings {
+                    if !scraped_ids.insert(listing.refnr.clone()) {
+                        continue;
+                    }
+                    yield convert(&client, &base_url, listing.into()).await;
+                }
+                if count < PAGE_SIZE {
+                    log::info!("No more results for query: {} after page: {}", query.keywords, page);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+pub struct ArbeitsagenturSource {
+    pub base_url: String,
+}
+
+impl Default for ArbeitsagenturSource {
+    fn default() -> Self {
+        Self {
+            base_url: base_url("ARBEITSAGENTUR_BASE_URL", BASE_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl JobSource for ArbeitsagenturSource {
+    fn name(&self) -> &'static str {
+        "arbeitsagentur"
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        scrape(self.base_url.clone(), params.queries, params.known_ids)
+            .await
+            .map(Ok)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::query::Recency;
+
+    #[test]
+    fn test_job_search_url() {
+        let query = SearchQuery {
+            location: Some("München".to_owned()),
+            radius: Some(25),
+            recency: Some(Recency::Week),
+            employment_type: Some(EmploymentType::FullTime),
+            remote: true,
+            ..SearchQuery::new("Rust Entwickler")
+        };
+        assert_eq!(
+            job_search_url(BASE_URL, &query, 2),
+            "https://rest.arbeitsagentur.de/jobboerse/jobsuche-service/pc/v4/jobs?\
+             was=Rust+Entwickler&page=2&size=100&wo=M%C3%BCnchen&umkreis=25\
+             &veroeffentlichtseit=7&arbeitszeit=vz%3Bho"
+        );
+    }
+
+    #[test]
+    fn test_job_details_url() {
+        assert_eq!(
+            job_details_url(BASE_URL, "10000-1186541843-S"),
+            "https://rest.arbeitsagentur.de/jobboerse/jobsuche-service/pc/v4/jobdetails/\
+             MTAwMDAtMTE4NjU0MTg0My1T"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_from_mock() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 130,
+            malformed_every: Some(50),
+            ..mock_server::Config::default()
+        })
+        .await;
+        let client = Client::builder(reqwest::Client::new())
+            .default_headers(api_headers())
+            .default_rate_limit(crate::client::RateLimit {
+                requests_per_second: 500.0,
+                burst: 50,
+            })
+            .build();
+        let known = HashSet::from(["10000-0000000000-S".to_owned()]);
+        let jobs = scrape_with(
+            client,
+            server.arbeitsagentur_url(),
+            vec![SearchQuery::new("Rust")],
+            known,
+        )
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(jobs.len(), 129);
+        assert_eq!(server.posting_requests(), 129);
+        let without_details = jobs
+            .iter()
+            .filter(|job| matches!(job, crate::Job::Arbeitsagentur { raw_data: None, .. }))
+            .count();
+        assert_eq!(without_details, 2);
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+
+use crate::client::Client;
+use crate::headers;
+use crate::source::{JobStream, SourceError};
+use crate::Job;
+
+/// Applicant tracking system hosting the career sites of many companies.
+/// Their apis serve every posting of a company with its description, so companies
+/// are scraped completely, the search queries don't apply and postings in
+/// `known_ids` are only left out
+#[async_trait]
+pub(crate) trait Ats: Clone + Send + Sync + 'static {
+    /// Name of the source, used in the logs
+    const NAME: &'static str;
+    /// Setting asked for by `SourceError::MissingConfig` when there are no companies
+    const CONFIG: &'static str;
+    type Error: Display + Send;
+
+    /// Companies to scrape, as they appear in the urls of the system
+    fn companies(&self) -> &[String];
+
+    /// Every posting of `company`
+    async fn company_jobs(&self, client: &Client, company: &str) -> Result<Vec<Job>, Self::Error>;
+}
+
+/// Postings of every company, a company that can't be scraped is skipped
+pub(crate) fn scrape<A: Ats>(
+    ats: A,
+    client: Client,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = Job> {
+    let mut scraped_ids = known_ids;
+    async_stream::stream! {
+        for company in ats.companies() {
+            let jobs = match ats.company_jobs(&client, company).await {
+                Ok(jobs) => jobs,
+                Err(e) => {
+                    log::error!("Skipping {} company: {}, error: {}", A::NAME, company, e);
+                    continue;
+                }
+            };
+            for job in jobs {
+                if scraped_ids.insert(job.site_id()) {
+                    yield job;
+                }
+            }
+        }
+    }
+}
+
+/// `JobSource::jobs` of an applicant tracking system, fails without any company
+pub(crate) fn jobs<A: Ats>(ats: &A, known_ids: HashSet<String>) -> JobStream {
+    if ats.companies().is_empty() {
+        let error = SourceError::MissingConfig(A::CONFIG);
+        return futures::stream::once(async { Err(error) }).boxed();
+    }
+    let client = Client::builder(reqwest::Client::new())
+        .profile(headers::random())
+        .build();
+    scrape(ats.clone(), client, known_ids).map(Ok).boxed()
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Storage for the cursors of running searches, identified by source and search.
+/// Failures are logged by the store, a lost checkpoint only means the search starts over
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    async fn load(&self, source: &str, search: &str) -> Option<Value>;
+
+    async fn save(&self, source: &str, search: &str, cursor: Value);
+
+    /// Called once a search is complete
+    async fn remove(&self, source: &str, search: &str);
+}
+
+/// Keeps the checkpoints in memory, they are gone with the process
+#[derive(Default)]
+pub struct MemoryCheckpoints {
+    cursors: Mutex<HashMap<(String, String), Value>>,
+}
+
+impl MemoryCheckpoints {
+    pub fn len(&self) -> usize {
+        self.cursors.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for MemoryCheckpoints {
+    async fn load(&self, source: &str, search: &str) -> Option<Value> {
+        let key = (source.to_owned(), search.to_owned());
+        self.cursors.lock().unwrap().get(&key).cloned()
+    }
+
+    async fn save(&self, source: &str, search: &str, cursor: Value) {
+        let key = (source.to_owned(), search.to_owned());
+        self.cursors.lock().unwrap().insert(key, cursor);
+    }
+
+    async fn remove(&self, source: &str, search: &str) {
+        let key = (source.to_owned(), search.to_owned());
+        self.cursors.lock().unwrap().remove(&key);
+    }
+}
+
+/// Checkpoints of a scrape, sources save their cursor after every page they finished.
+/// Without a store nothing is saved
+#[derive(Clone, Default)]
+pub struct Checkpoints {
+    store: Option<Arc<dyn CheckpointStore>>,
+    /// Continue from the saved cursors instead of starting every search from the beginning
+    resume: bool,
+}
+
+impl Checkpoints {
+    pub fn new(store: Arc<dyn CheckpointStore>, resume: bool) -> Self {
+        Self {
+            store: Some(store),
+            resume,
+        }
+    }
+
+    /// Cursor the last run saved for `search`, `None` unless resuming
+    pub(crate) async fn resume<T: DeserializeOwned>(
+        &self,
+        source: &str,
+        search: &str,
+    ) -> Option<T> {
+        if !self.resume {
+            return None;
+        }
+        let cursor = self.store.as_ref()?.load(source, search).await?;
+        match serde_json::from_value(cursor) {
+            Ok(cursor) => {
+                log::info!("Resuming {} search {} from its checkpoint", source, search);
+                Some(cursor)
+            }
+            Err(e) => {
+                log::warn!(
+                    "Ignoring invalid checkpoint of {} search {}: {}",
+                    source,
+                    search,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    pub(crate) async fn save<T: Serialize>(&self, source: &str, search: &str, cursor: &T) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        match serde_json::to_value(cursor) {
+            Ok(cursor) => store.save(source, search, cursor).await,
+            Err(e) => log::error!("Failed to serialize the cursor of {}: {}", source, e),
+        }
+    }
+
+    pub(crate) async fn finish(&self, source: &str, search: &str) {
+        if let Some(store) = &self.store {
+            store.remove(source, search).await;
+        }
+    }
+}
+
+impl std::fmt::Debug for Checkpoints {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Checkpoints")
+            .field("enabled", &self.store.is_some())
+            .field("resume", &self.resume)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_saved_cursors_are_only_used_when_resuming() {
+        let store = Arc::new(MemoryCheckpoints::default());
+        let fresh = Checkpoints::new(store.clone(), false);
+        fresh.save("linkedin", "rust", &50).await;
+        assert_eq!(fresh.resume::<u32>("linkedin", "rust").await, None);
+
+        let resumed = Checkpoints::new(store.clone(), true);
+        assert_eq!(resumed.resume::<u32>("linkedin", "rust").await, Some(50));
+        assert_eq!(resumed.resume::<String>("linkedin", "rust").await, None);
+        resumed.finish("linkedin", "rust").await;
+        assert!(store.is_empty());
+    }
+}
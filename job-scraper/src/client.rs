@@ -0,0 +1,631 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, COOKIE, RETRY_AFTER};
+use reqwest::{IntoUrl, Method, Request, Response, StatusCode};
+use serde::Serialize;
+use tokio::time::{sleep, Instant};
+
+use crate::cookies::CookieJar;
+use crate::fixtures::{Fixtures, Mode};
+use crate::headers::{Destination, Profile};
+use crate::proxy::{self, Outcome, Proxy, ProxyPool};
+use crate::robots::{self, RobotsCache};
+use crate::stats::{self, RunStats};
+
+/// Token bucket settings for a single host
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub requests_per_second: f64,
+    /// Requests that can be sent at once after the host has been idle
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub fn per_second(requests_per_second: f64) -> Self {
+        Self {
+            requests_per_second,
+            burst: 1,
+        }
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 2.0,
+            burst: 4,
+        }
+    }
+}
+
+/// Retry settings for requests that are rate limited or fail to connect
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub max_retries: u32,
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base: Duration::from_secs(2),
+            max: Duration::from_secs(120),
+        }
+    }
+}
+
+impl Backoff {
+    /// Exponential delay for the given retry attempt, starting at 0, jittered by up to half
+    fn delay(&self, attempt: u32) -> Duration {
+        let exponential = self.base.saturating_mul(2u32.saturating_pow(attempt));
+        let capped = exponential.min(self.max);
+        capped.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    /// Set when the host told us to back off, no requests are sent before
+    blocked_until: Option<Instant>,
+}
+
+struct RateLimiter {
+    default: RateLimit,
+    limits: Mutex<HashMap<String, RateLimit>>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    fn limit(&self, host: &str) -> RateLimit {
+        let limits = self.limits.lock().unwrap();
+        limits.get(host).copied().unwrap_or(self.default)
+    }
+
+    /// Lowers the limit of `host`, a limit that is already stricter is kept
+    fn restrict(&self, host: &str, limit: RateLimit) {
+        let current = self.limit(host);
+        if limit.requests_per_second < current.requests_per_second {
+            self.limits.lock().unwrap().insert(host.to_owned(), limit);
+        }
+    }
+
+    /// Waits until a request to `host` may be sent
+    async fn acquire(&self, host: &str) {
+        let limit = self.limit(host);
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let now = Instant::now();
+                let bucket = buckets.entry(host.to_owned()).or_insert_with(|| Bucket {
+                    tokens: limit.burst as f64,
+                    last_refill: now,
+                    blocked_until: None,
+                });
+                let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                bucket.tokens =
+                    (bucket.tokens + elapsed * limit.requests_per_second).min(limit.burst as f64);
+                bucket.last_refill = now;
+                match bucket.blocked_until {
+                    Some(until) if until > now => until - now,
+                    _ if bucket.tokens >= 1.0 => {
+                        bucket.tokens -= 1.0;
+                        return;
+                    }
+                    _ => Duration::from_secs_f64((1.0 - bucket.tokens) / limit.requests_per_second),
+                }
+            };
+            sleep(wait).await;
+        }
+    }
+
+    /// Stops all requests to `host` for the given duration
+    fn block(&self, host: &str, duration: Duration) {
+        let mut buckets = self.buckets.lock().unwrap();
+        if let Some(bucket) = buckets.get_mut(host) {
+            let until = Instant::now() + duration;
+            if bucket.blocked_until.is_none_or(|blocked| blocked < until) {
+                bucket.blocked_until = Some(until);
+            }
+        }
+    }
+}
+
+/// Parses the `Retry-After` header, either delay seconds or a http date
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let value = resp.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+fn should_retry(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
+}
+
+fn outcome(result: &reqwest::Result<Response>) -> Outcome {
+    match result {
+        Ok(resp) if should_retry(resp.status()) => Outcome::RateLimited,
+        Ok(_) => Outcome::Success,
+        Err(_) => Outcome::Failed,
+    }
+}
+
+/// reqwest::Client wrapper shared by all scrapers, throttles requests per host
+/// and retries rate limited requests with exponential backoff.
+/// Requests go through the proxies of `SCRAPER_PROXIES` if it's set,
+/// with `SCRAPER_RESPECT_ROBOTS` GET requests follow the robots.txt of their host
+#[derive(Clone)]
+pub struct Client {
+    inner: reqwest::Client,
+    profile: Option<&'static Profile>,
+    /// Applied here instead of on `inner`, proxied requests are sent by other clients
+    default_headers: HeaderMap,
+    limiter: Arc<RateLimiter>,
+    backoff: Backoff,
+    fixtures: Option<Arc<Fixtures>>,
+    proxies: Option<Arc<ProxyPool>>,
+    robots: Option<Arc<RobotsCache>>,
+    cookies: Option<Arc<CookieJar>>,
+    stats: Option<Arc<RunStats>>,
+}
+
+impl Client {
+    pub fn new(inner: reqwest::Client) -> Self {
+        Self::builder(inner).build()
+    }
+
+    pub fn builder(inner: reqwest::Client) -> ClientBuilder {
+        ClientBuilder {
+            inner,
+            default_limit: RateLimit::default(),
+            limits: HashMap::new(),
+            backoff: Backoff::default(),
+            fixtures: None,
+            profile: None,
+            default_headers: HeaderMap::new(),
+            proxies: proxy::shared(),
+            robots: robots::shared(),
+            cookies: None,
+            stats: Some(stats::shared()),
+        }
+    }
+
+    pub fn get(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Counts a posting whose content couldn't be extracted from the fetched page
+    pub(crate) fn extraction_failed(&self) {
+        if let Some(stats) = &self.stats {
+            stats.extraction_failed();
+        }
+    }
+
+    pub fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        RequestBuilder {
+            client: self.clone(),
+            inner: self.inner.request(method, url),
+            destination: Destination::default(),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Applies the crawl delay of the host, a disallowed request is answered
+    /// with a `403 Forbidden` that never left the client
+    async fn check_robots(
+        &self,
+        cache: &RobotsCache,
+        host: &str,
+        request: &Request,
+    ) -> Option<Response> {
+        let headers = self
+            .profile
+            .map(|profile| profile.headers(Destination::Document))
+            .unwrap_or_default();
+        let robots = cache
+            .get(request.url(), |robots_url| async move {
+                let request = self.inner.get(robots_url).headers(headers).build()?;
+                self.attempt(host, request).await.1
+            })
+            .await;
+        if let Some(delay) = robots.crawl_delay() {
+            self.limiter.restrict(
+                host,
+                RateLimit {
+                    requests_per_second: 1.0 / delay.as_secs_f64(),
+                    burst: 1,
+                },
+            );
+        }
+        if robots.is_allowed(request.url()) {
+            return None;
+        }
+        cache.skip(request.url());
+        let resp = http::Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .body(String::new())
+            .unwrap();
+        Some(resp.into())
+    }
+
+    /// Sends `request` once, throttled per host and through a proxy of the pool if there is one
+    async fn attempt(
+        &self,
+        host: &str,
+        request: Request,
+    ) -> (Option<Arc<Proxy>>, reqwest::Result<Response>) {
+        self.limiter.acquire(host).await;
+        let proxy = self.proxies.as_ref().and_then(|pool| pool.select(host));
+        let result = match &proxy {
+            Some(proxy) => proxy.client.execute(request).await,
+            None => self.inner.execute(request).await,
+        };
+        if let (Some(pool), Some(proxy)) = (&self.proxies, &proxy) {
+            pool.report(proxy, host, outcome(&result));
+        }
+        if let (Some(stats), Ok(resp)) = (&self.stats, &result) {
+            stats.record(resp.status());
+        }
+        (proxy, result)
+    }
+
+    async fn execute(&self, request: Request) -> reqwest::Result<Response> {
+        if let Some(fixtures) = self.fixtures.as_ref() {
+            if fixtures.mode() == Mode::Replay {
+                return Ok(fixtures.replay_response(&request));
+            }
+        }
+        let host = request.url().host_str().unwrap_or_default().to_owned();
+        if let Some(robots) = self.robots.as_ref() {
+            if request.method() == Method::GET {
+                if let Some(skipped) = self.check_robots(robots, &host, &request).await {
+                    return Ok(skipped);
+                }
+            }
+        }
+        let mut attempt = 0;
+        loop {
+            // bodies built through this wrapper are always in memory and can be cloned
+            let retry = request.try_clone().expect("Request body must be clonable");
+            let (proxy, result) = self.attempt(&host, retry).await;
+            let can_retry = attempt < self.backoff.max_retries;
+            let delay = match &result {
+                // another proxy might not be limited yet, no need to wait
+                Ok(resp) if should_retry(resp.status()) && can_retry && proxy.is_some() => {
+                    log::info!(
+                        "Rate limited by {} through {} (Status {}), retrying",
+                        host,
+                        proxy.as_ref().unwrap().name(),
+                        resp.status()
+                    );
+                    Duration::ZERO
+                }
+                Ok(resp) if should_retry(resp.status()) && can_retry => {
+                    let delay = retry_after(resp).unwrap_or_else(|| self.backoff.delay(attempt));
+                    log::info!(
+                        "Rate limited by {} (Status {}), retrying in {:?}",
+                        host,
+                        resp.status(),
+                        delay
+                    );
+                    self.limiter.block(&host, delay);
+                    delay
+                }
+                Err(e) if (e.is_timeout() || e.is_connect()) && can_retry => {
+                    let delay = self.backoff.delay(attempt);
+                    log::info!("Request to {} failed: {}, retrying in {:?}", host, e, delay);
+                    delay
+                }
+                _ => {
+                    if let (Some(jar), Ok(resp)) = (&self.cookies, &result) {
+                        jar.update(resp.headers());
+                    }
+                    return match (&self.fixtures, result) {
+                        (Some(fixtures), Ok(resp)) => {
+                            fixtures.record_response(&request, resp).await
+                        }
+                        (_, result) => result,
+                    };
+                }
+            };
+            attempt += 1;
+            sleep(delay).await;
+        }
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new(reqwest::Client::new())
+    }
+}
+
+pub struct ClientBuilder {
+    inner: reqwest::Client,
+    default_limit: RateLimit,
+    limits: HashMap<String, RateLimit>,
+    backoff: Backoff,
+    fixtures: Option<Fixtures>,
+    profile: Option<&'static Profile>,
+    default_headers: HeaderMap,
+    proxies: Option<Arc<ProxyPool>>,
+    robots: Option<Arc<RobotsCache>>,
+    cookies: Option<Arc<CookieJar>>,
+    stats: Option<Arc<RunStats>>,
+}
+
+impl ClientBuilder {
+    /// Limit for every host without a specific limit
+    pub fn default_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.default_limit = limit;
+        self
+    }
+
+    pub fn rate_limit(mut self, host: &str, limit: RateLimit) -> Self {
+        self.limits.insert(host.to_owned(), limit);
+        self
+    }
+
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Browser the requests pretend to come from, see `headers::random`
+    pub fn profile(mut self, profile: &'static Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Headers sent with every request, these take precedence over the profile
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Replaces the pool configured through the environment, `None` sends requests directly
+    pub fn proxies(mut self, proxies: Option<Arc<ProxyPool>>) -> Self {
+        self.proxies = proxies;
+        self
+    }
+
+    /// Replaces the cache enabled through the environment, `None` ignores robots.txt
+    pub fn robots(mut self, robots: Option<Arc<RobotsCache>>) -> Self {
+        self.robots = robots;
+        self
+    }
+
+    /// Sends the cookies of the jar and keeps it up to date with the responses
+    pub fn cookies(mut self, cookies: Arc<CookieJar>) -> Self {
+        self.cookies = Some(cookies);
+        self
+    }
+
+    /// Replaces the stats shared by all clients, `None` doesn't count the responses
+    pub fn stats(mut self, stats: Option<Arc<RunStats>>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Records or replays all traffic of the client, see `Fixtures`
+    pub fn fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    pub fn build(self) -> Client {
+        Client {
+            inner: self.inner,
+            limiter: Arc::new(RateLimiter {
+                default: self.default_limit,
+                limits: Mutex::new(self.limits),
+                buckets: Mutex::new(HashMap::new()),
+            }),
+            backoff: self.backoff,
+            fixtures: self.fixtures.map(Arc::new),
+            profile: self.profile,
+            default_headers: self.default_headers,
+            proxies: self.proxies,
+            robots: self.robots,
+            cookies: self.cookies,
+            stats: self.stats,
+        }
+    }
+}
+
+pub struct RequestBuilder {
+    client: Client,
+    inner: reqwest::RequestBuilder,
+    destination: Destination,
+    /// Replace the profile and default headers of the same name
+    headers: HeaderMap,
+}
+
+impl RequestBuilder {
+    pub fn header(mut self, key: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(key, value);
+        self
+    }
+
+    /// Selects the profile headers, requests load a `Document` unless set otherwise
+    pub fn destination(self, destination: Destination) -> Self {
+        Self {
+            destination,
+            ..self
+        }
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        Self {
+            inner: self.inner.json(json),
+            ..self
+        }
+    }
+
+    pub async fn send(self) -> reqwest::Result<Response> {
+        let mut headers = self
+            .client
+            .profile
+            .map(|profile| profile.headers(self.destination))
+            .unwrap_or_default();
+        headers.extend(self.client.default_headers.clone());
+        if let Some(cookie) = self
+            .client
+            .cookies
+            .as_ref()
+            .and_then(|jar| jar.header_value())
+        {
+            headers.insert(COOKIE, cookie);
+        }
+        headers.extend(self.headers);
+        let request = self.inner.headers(headers).build()?;
+        self.client.execute(request).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_is_capped() {
+        let backoff = Backoff {
+            max_retries: 10,
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+        };
+        assert!(backoff.delay(0) <= Duration::from_secs(1));
+        assert!(backoff.delay(0) >= Duration::from_millis(500));
+        assert!(backoff.delay(8) <= Duration::from_secs(10));
+        assert!(backoff.delay(8) >= Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_requests() {
+        let limiter = RateLimiter {
+            default: RateLimit {
+                requests_per_second: 20.0,
+                burst: 2,
+            },
+            limits: Mutex::new(HashMap::new()),
+            buckets: Mutex::new(HashMap::new()),
+        };
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire("example.com").await;
+        }
+        // two requests from the burst, the other two spaced by 50ms each
+        assert!(start.elapsed() >= Duration::from_millis(95));
+
+        limiter.block("example.com", Duration::from_millis(200));
+        let blocked = Instant::now();
+        limiter.acquire("example.com").await;
+        assert!(blocked.elapsed() >= Duration::from_millis(200));
+        // other hosts aren't affected
+        let other = Instant::now();
+        limiter.acquire("other.com").await;
+        assert!(other.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limited_requests() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 10,
+            rate_limit_every: Some(2),
+            ..mock_server::Config::default()
+        })
+        .await;
+        let client = Client::default();
+        let url = format!("{}/jobs/api/search?offset=0&limit=5", server.xing_url());
+        for _ in 0..3 {
+            let resp = client.get(&url).send().await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skips_dead_proxies() {
+        let server = mock_server::MockServer::start(mock_server::Config::default()).await;
+        let proxies = ProxyPool::new(
+            &["http://127.0.0.1:1".to_owned(), server.url()],
+            proxy::Rotation::Request,
+        )
+        .unwrap()
+        .max_failures(1);
+        let client = Client::builder(reqwest::Client::new())
+            .backoff(Backoff {
+                max_retries: 3,
+                base: Duration::from_millis(1),
+                max: Duration::from_millis(1),
+            })
+            .proxies(Some(Arc::new(proxies)))
+            .build();
+        // the mock server acts as a http proxy, the host never has to resolve
+        let url = "http://jobs.example.invalid/jobs/api/search?offset=0&limit=5";
+        for _ in 0..3 {
+            let resp = client.get(url).send().await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+        let proxies = client.proxies.as_ref().unwrap().stats();
+        assert!(!proxies[0].healthy);
+        assert_eq!(proxies[1].successes, 3);
+    }
+
+    #[tokio::test]
+    async fn test_follows_robots_txt() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            robots_txt: Some("User-agent: *\nDisallow: /jobs/\nCrawl-delay: 0.2\n".to_owned()),
+            ..mock_server::Config::default()
+        })
+        .await;
+        let robots = Arc::new(RobotsCache::new());
+        let stats = Arc::new(RunStats::new());
+        let client = Client::builder(reqwest::Client::new())
+            .default_rate_limit(RateLimit {
+                requests_per_second: 100.0,
+                burst: 10,
+            })
+            .robots(Some(robots.clone()))
+            .stats(Some(stats.clone()))
+            .build();
+        let url = format!("{}/jobs/api/search?offset=0&limit=5", server.xing_url());
+        let resp = client.get(&url).send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        // robots.txt is fetched like any other page, the skipped request never left
+        assert_eq!(stats.take().pages, 1);
+        // only GET requests are checked
+        let resp = client
+            .post(format!("{}/candidate/api/v1/job_suggestions", server.url()))
+            .header(COOKIE, HeaderValue::from_static("_instaffo_session=test"))
+            .json(&serde_json::json!({}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(robots.skipped()["127.0.0.1"], 1);
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            let url = format!(
+                "{}/jobs-guest/jobs/api/seeMoreJobPostings/search",
+                server.url()
+            );
+            client.get(&url).send().await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+}
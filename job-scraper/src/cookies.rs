@@ -1,3 +1,26 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use reqwest::header::{HeaderMap, HeaderValue, SET_COOKIE};
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Directory the cookie jars are stored in, `cookies` in the working directory by default
+pub const COOKIE_DIR_ENV: &str = "SCRAPER_COOKIE_DIR";
+const DEFAULT_COOKIE_DIR: &str = "cookies";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to access cookie jar: '{0}'")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid cookie jar: '{0}'")]
+    Json(#[from] serde_json::Error),
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
 pub(crate) fn combine_cookies<I>(pairs: I) -> String
 where
     I: Iterator<Item = (String, String)>,
@@ -8,3 +31,195 @@ where
         .join(";");
     return encoded_cookies;
 }
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stored {
+    /// Last value passed to `CookieJar::seed`, a different one replaces the stored session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<String>,
+    cookies: BTreeMap<String, String>,
+}
+
+/// Cookies of a single site that outlive the run. Every `Set-Cookie` the site sends
+/// is written back to the file, so a scheduled run continues with the session of the last one
+pub struct CookieJar {
+    path: PathBuf,
+    stored: Mutex<Stored>,
+}
+
+impl CookieJar {
+    /// Loads the jar stored at `path`, a missing file is an empty jar
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let stored = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Stored::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            stored: Mutex::new(stored),
+        })
+    }
+
+    /// Jar `{name}.json` in the directory of `SCRAPER_COOKIE_DIR`
+    pub fn from_env(name: &str) -> Result<Self> {
+        Self::open(Self::env_path(name))
+    }
+
+    /// Path `from_env` opens the jar of `name` at
+    pub fn env_path(name: &str) -> PathBuf {
+        let dir = std::env::var(COOKIE_DIR_ENV).unwrap_or_else(|_| DEFAULT_COOKIE_DIR.to_owned());
+        Path::new(&dir).join(format!("{}.json", name))
+    }
+
+    /// Starts a new session from a cookie given by the user, e.g. through an env variable.
+    /// Passing the same value as in an earlier run keeps the cookies that were updated since
+    pub fn seed(&self, name: &str, value: &str) {
+        let mut stored = self.stored.lock().unwrap();
+        if stored.seed.as_deref() == Some(value) {
+            return;
+        }
+        log::info!("Starting a new session from the given {} cookie", name);
+        stored.cookies.clear();
+        stored
+            .cookies
+            .insert(name.to_owned(), urlencoding::encode(value).into_owned());
+        stored.seed = Some(value.to_owned());
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.stored.lock().unwrap().cookies.get(name).cloned()
+    }
+
+    pub(crate) fn header_value(&self) -> Option<HeaderValue> {
+        let stored = self.stored.lock().unwrap();
+        if stored.cookies.is_empty() {
+            return None;
+        }
+        let cookies = combine_cookies(stored.cookies.clone().into_iter());
+        HeaderValue::from_str(&cookies).ok()
+    }
+
+    /// Applies the `Set-Cookie` headers of a response and saves the jar if anything changed
+    pub(crate) fn update(&self, headers: &HeaderMap) {
+        let mut changed = false;
+        {
+            let mut stored = self.stored.lock().unwrap();
+            for set_cookie in headers.get_all(SET_COOKIE) {
+                let Some((name, value, expired)) =
+                    set_cookie.to_str().ok().and_then(parse_set_cookie)
+                else {
+                    continue;
+                };
+                changed |= if expired {
+                    stored.cookies.remove(&name).is_some()
+                } else {
+                    stored.cookies.insert(name, value.clone()).as_ref() != Some(&value)
+                };
+            }
+        }
+        if changed {
+            if let Err(e) = self.save() {
+                log::error!("Failed to save cookies to {:?}: {}", self.path, e);
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(&*self.stored.lock().unwrap())?;
+        std::fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+/// Name and value of a `Set-Cookie` header and whether it deletes the cookie
+fn parse_set_cookie(header: &str) -> Option<(String, String, bool)> {
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let expired = parts.any(|attribute| {
+        let Some((key, value)) = attribute.split_once('=') else {
+            return false;
+        };
+        let value = value.trim();
+        match key.trim().to_lowercase().as_str() {
+            "max-age" => value.parse::<i64>().is_ok_and(|max_age| max_age <= 0),
+            "expires" => chrono::DateTime::parse_from_rfc2822(value)
+                .is_ok_and(|expires| expires < chrono::Utc::now()),
+            _ => false,
+        }
+    });
+    Some((name.to_owned(), value.trim().to_owned(), expired))
+}
+
+/// Sites answer requests of an expired session with 401 or send them to their login page
+pub fn is_session_expired(resp: &Response) -> bool {
+    let path = resp.url().path().to_lowercase();
+    resp.status() == StatusCode::UNAUTHORIZED || path.contains("login") || path.contains("sign_in")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn jar_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cookies-{}-{}.json", name, std::process::id()))
+    }
+
+    fn set_cookies(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(SET_COOKIE, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_parse_set_cookie() {
+        assert_eq!(
+            parse_set_cookie("_session=abc%3D; path=/; HttpOnly; SameSite=Lax"),
+            Some(("_session".to_owned(), "abc%3D".to_owned(), false))
+        );
+        assert_eq!(
+            parse_set_cookie("_session=; Max-Age=0"),
+            Some(("_session".to_owned(), "".to_owned(), true))
+        );
+        assert_eq!(
+            parse_set_cookie("_session=x; Expires=Thu, 01 Jan 1970 00:00:00 GMT"),
+            Some(("_session".to_owned(), "x".to_owned(), true))
+        );
+        assert_eq!(parse_set_cookie("garbage"), None);
+    }
+
+    #[test]
+    fn test_updates_are_persisted() {
+        let path = jar_path("persisted");
+        let jar = CookieJar::open(&path).unwrap();
+        jar.seed("_session", "first session");
+        assert_eq!(jar.header_value().unwrap(), "_session=first%20session");
+        jar.update(&set_cookies(&["_session=renewed; path=/", "tracking=1"]));
+
+        let reopened = CookieJar::open(&path).unwrap();
+        // the same seed continues the renewed session
+        reopened.seed("_session", "first session");
+        assert_eq!(reopened.get("_session").as_deref(), Some("renewed"));
+        assert_eq!(reopened.get("tracking").as_deref(), Some("1"));
+        reopened.update(&set_cookies(&["tracking=; Max-Age=0"]));
+        assert_eq!(CookieJar::open(&path).unwrap().get("tracking"), None);
+
+        // a new seed replaces the stored session
+        reopened.seed("_session", "second session");
+        assert_eq!(
+            reopened.header_value().unwrap(),
+            "_session=second%20session"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Set this variable to record fixtures from the live sites instead of replaying them
+pub const RECORD_ENV: &str = "RECORD_FIXTURES";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to access fixture file: '{0}'")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid fixture file: '{0}'")]
+    Json(#[from] serde_json::Error),
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Requests are sent and every response is written to the fixture file
+    Record,
+    /// Responses are served from the fixture file, nothing is sent
+    Replay,
+}
+
+/// A recorded request and the response that was served for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Exchange {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<String>,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    body: String,
+}
+
+impl Exchange {
+    fn matches(&self, method: &str, url: &str, request_body: &Option<String>) -> bool {
+        self.method == method && self.url == url && &self.request_body == request_body
+    }
+
+    fn response(&self) -> Response {
+        let mut response = http::Response::builder().status(self.status);
+        if let Some(content_type) = &self.content_type {
+            response = response.header(CONTENT_TYPE, content_type);
+        }
+        response
+            .body(self.body.clone())
+            .expect("Recorded response must be valid")
+            .into()
+    }
+}
+
+fn request_key(request: &Request) -> (String, String, Option<String>) {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+    (
+        request.method().to_string(),
+        request.url().to_string(),
+        body,
+    )
+}
+
+/// HTTP traffic of a scraper stored in a json file, lets tests run against
+/// recorded responses instead of the live sites.
+/// Only status, content type and body of a response are kept, cookies and
+/// other headers never end up in the file
+pub struct Fixtures {
+    mode: Mode,
+    path: PathBuf,
+    exchanges: Mutex<Vec<Exchange>>,
+}
+
+impl Fixtures {
+    /// Starts a new recording, the file at `path` is overwritten
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: Mode::Record,
+            path: path.into(),
+            exchanges: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let exchanges = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        Ok(Self {
+            mode: Mode::Replay,
+            path,
+            exchanges: Mutex::new(exchanges),
+        })
+    }
+
+    /// Records if `RECORD_FIXTURES` is set, replays otherwise
+    pub fn from_env(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        if std::env::var_os(RECORD_ENV).is_some() {
+            return Self::record(path);
+        }
+        Self::replay(&path).unwrap_or_else(|e| {
+            panic!(
+                "Can't replay {}: {}, record it by running the test with {}=1",
+                path.display(),
+                e,
+                RECORD_ENV
+            )
+        })
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Serves the recorded response for the request, 404 if it was never recorded
+    pub(crate) fn replay_response(&self, request: &Request) -> Response {
+        let (method, url, body) = request_key(request);
+        let exchanges = self.exchanges.lock().unwrap();
+        match exchanges.iter().find(|e| e.matches(&method, &url, &body)) {
+            Some(exchange) => exchange.response(),
+            None => {
+                log::error!(
+                    "No fixture recorded for {} {} in {}",
+                    method,
+                    url,
+                    self.path.display()
+                );
+                http::Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(format!("No fixture recorded for {} {}", method, url))
+                    .unwrap()
+                    .into()
+            }
+        }
+    }
+
+    /// Stores the response and hands back an identical one, the body can only be read once
+    pub(crate) async fn record_response(
+        &self,
+        request: &Request,
+        response: Response,
+    ) -> reqwest::Result<Response> {
+        let (method, url, request_body) = request_key(request);
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let body = response.text().await?;
+        let exchange = Exchange {
+            method,
+            url,
+            request_body,
+            status,
+            content_type,
+            body,
+        };
+        let response = exchange.response();
+        let mut exchanges = self.exchanges.lock().unwrap();
+        exchanges.retain(|e| !e.matches(&exchange.method, &exchange.url, &exchange.request_body));
+        exchanges.push(exchange);
+        if let Err(e) = save(&self.path, &exchanges) {
+            log::error!("Failed to save fixture {}: {}", self.path.display(), e);
+        }
+        Ok(response)
+    }
+}
+
+fn save(path: &Path, exchanges: &[Exchange]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(exchanges)?)?;
+    Ok(())
+}
+
+/// Location of a fixture checked into `job-scraper/fixtures`
+#[cfg(test)]
+pub(crate) fn path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(format!("{}.json", name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replay_matches_method_url_and_body() {
+        let fixtures = Fixtures {
+            mode: Mode::Replay,
+            path: PathBuf::from("test.json"),
+            exchanges: Mutex::new(vec![Exchange {
+                method: "POST".to_owned(),
+                url: "https://example.com/api".to_owned(),
+                request_body: Some(r#"{"page":2}"#.to_owned()),
+                status: 200,
+                content_type: Some("application/json".to_owned()),
+                body: r#"{"items":[]}"#.to_owned(),
+            }]),
+        };
+        let client = reqwest::Client::new();
+        let request = client
+            .post("https://example.com/api")
+            .body(r#"{"page":2}"#)
+            .build()
+            .unwrap();
+        let response = fixtures.replay_response(&request);
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        assert_eq!(response.text().await.unwrap(), r#"{"items":[]}"#);
+
+        let request = client
+            .post("https://example.com/api")
+            .body(r#"{"page":3}"#)
+            .build()
+            .unwrap();
+        assert_eq!(fixtures.replay_response(&request).status(), 404);
+    }
+}
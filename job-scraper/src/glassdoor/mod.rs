@@ -0,0 +1,352 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::client::{Client, RateLimit};
+use crate::headers;
+use crate::markdown;
+use crate::query::{url_with_params, EmploymentType, SearchQuery};
+use crate::source::{JobSource, JobStream, SearchParams};
+
+type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Request error: '{0}'")]
+    Request(#[from] reqwest::Error),
+    #[error("Failed to scrape data from: '{0}'")]
+    RequestNotOk(String),
+    #[error("Content not found in html: '{0}'")]
+    ContentNotFound(&'static str),
+}
+
+/// Glassdoor only serves 30 result pages per search
+const MAX_PAGES: u32 = 30;
+
+const RATE_LIMIT: RateLimit = RateLimit {
+    requests_per_second: 1.0,
+    burst: 2,
+};
+
+/// Employer review summary shown next to every Glassdoor posting
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyRating {
+    rating_average: Option<f32>,
+    rating_count: Option<u32>,
+    recommend_to_friend: Option<f32>,
+    ceo_approval: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Company {
+    pub name: Option<String>,
+    rating: Option<CompanyRating>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Job {
+    pub glassdoor_id: String,
+    pub title: Option<String>,
+    location: Option<String>,
+    link: String,
+    pub company: Company,
+    /// `raw_data` as Markdown
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// What the posting page adds to a search result
+struct JobContent {
+    raw_data: String,
+    description: String,
+    rating: Option<CompanyRating>,
+}
+
+fn job_search_url(query: &SearchQuery, page: u32) -> String {
+    let mut params = vec![
+        ("sc.keyword", query.keywords.clone()),
+        ("p", page.to_string()),
+    ];
+    if let Some(location) = &query.location {
+        params.push(("locKeyword", location.clone()));
+    }
+    if let Some(days) = query.recency.and_then(|recency| recency.days()) {
+        params.push(("fromAge", days.to_string()));
+    }
+    if let Some(employment_type) = query.employment_type {
+        let job_type = match employment_type {
+            EmploymentType::FullTime => "fulltime",
+            EmploymentType::PartTime => "parttime",
+            EmploymentType::Freelance => "contract",
+            EmploymentType::Internship => "internship",
+            EmploymentType::Temporary => "temporary",
+        };
+        params.push(("jobType", job_type.to_owned()));
+    }
+    if query.remote {
+        params.push(("remoteWorkType", "1".to_owned()));
+    }
+    url_with_params("https://www.glassdoor.de/Job/jobs.htm", params)
+}
+
+fn absolute_url(href: &str) -> String {
+    if href.starts_with("http") {
+        href.to_owned()
+    } else {
+        format!("https://www.glassdoor.de{}", href)
+    }
+}
+
+fn select_text(el: ElementRef, selector: &Selector) -> Option<String> {
+    el.select(selector)
+        .next()
+        .map(|el| el.text().map(str::trim).collect::<String>())
+        .filter(|text| !text.is_empty())
+}
+
+/// Parses numbers like "4,1", "1.234" or "87 %" as shown on the german site
+fn parse_number(text: &str) -> Option<f32> {
+    let cleaned = text
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == ',' || *c == '.')
+        .collect::<String>();
+    if cleaned.contains(',') {
+        cleaned.replace('.', "").replace(',', ".").parse().ok()
+    } else if cleaned.matches('.').count() == 1 && cleaned.split('.').nth(1)?.len() < 3 {
+        cleaned.parse().ok()
+    } else {
+        cleaned.replace('.', "").parse().ok()
+    }
+}
+
+fn parse_search_page(body: &str) -> Vec<Job> {
+    let doc = Html::parse_document(body);
+    let item_selector = Selector::parse(r#"li[data-test="jobListing"]"#).unwrap();
+    let title_selector = Selector::parse(r#"a[data-test="job-title"]"#).unwrap();
+    let employer_selector = Selector::parse(r#"[data-test="employer-name"]"#).unwrap();
+    let location_selector = Selector::parse(r#"[data-test="emp-location"]"#).unwrap();
+    let rating_selector = Selector::parse(r#"[data-test="rating"]"#).unwrap();
+    doc.select(&item_selector)
+        .filter_map(|item| {
+            let glassdoor_id = item.value().attr("data-jobid")?.to_owned();
+            let title_el = item.select(&title_selector).next();
+            let link = title_el
+                .and_then(|el| el.value().attr("href"))
+                .map(absolute_url)
+                .unwrap_or_else(|| {
+                    format!(
+                        "https://www.glassdoor.de/job-listing/index.htm?jl={}",
+                        glassdoor_id
+                    )
+                });
+            let title = title_el.map(|el| el.text().map(str::trim).collect::<String>());
+            let rating = select_text(item, &rating_selector)
+                .and_then(|rating| parse_number(&rating))
+                .map(|rating_average| CompanyRating {
+                    rating_average: Some(rating_average),
+                    ..Default::default()
+                });
+            Some(Job {
+                glassdoor_id,
+                title,
+                location: select_text(item, &location_selector),
+                link,
+                company: Company {
+                    name: select_text(item, &employer_selector),
+                    rating,
+                },
+                description: None,
+            })
+        })
+        .collect()
+}
+
+/// Extracts the description and the employer review summary from a posting page
+fn parse_job_content(body: &str) -> Result<JobContent> {
+    let doc = Html::parse_document(body);
+    let root = doc.root_element();
+    let description_selector = Selector::parse(".jobDescriptionContent").unwrap();
+    let description = doc
+        .select(&description_selector)
+        .next()
+        .ok_or(Error::ContentNotFound("Job Posting data"))?;
+    let raw_data = description
+        .text()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let number = |selector: &str| {
+        let selector = Selector::parse(selector).unwrap();
+        select_text(root, &selector).and_then(|text| parse_number(&text))
+    };
+    let rating = CompanyRating {
+        rating_average: number(r#"[data-test="employer-rating"]"#),
+        rating_count: number(r#"[data-test="employer-review-count"]"#).map(|count| count as u32),
+        recommend_to_friend: number(r#"[data-test="recommend-to-friend"]"#),
+        ceo_approval: number(r#"[data-test="ceo-approval"]"#),
+    };
+    let rating = match rating {
+        CompanyRating {
+            rating_average: None,
+            rating_count: None,
+            recommend_to_friend: None,
+            ceo_approval: None,
+        } => None,
+        rating => Some(rating),
+    };
+    Ok(JobContent {
+        raw_data,
+        description: markdown::from_element(description),
+        rating,
+    })
+}
+
+async fn scrape_search_page(client: &Client, query: &SearchQuery, page: u32) -> Result<Vec<Job>> {
+    let url = job_search_url(query, page);
+    log::debug!(
+        "requesting jobs from glassdoor, page: {}, search: {}",
+        page,
+        query.keywords
+    );
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        log::error!(
+            "failed to retrieve results for page: {}, search: {}, status: {}",
+            page,
+            query.keywords,
+            resp.status()
+        );
+        return Err(Error::RequestNotOk(url));
+    }
+    let body = resp.text().await?;
+    Ok(parse_search_page(&body))
+}
+
+async fn scrape_job_content(client: &Client, job_url: &str) -> Result<JobContent> {
+    let resp = client.get(job_url).send().await?;
+    if !resp.status().is_success() {
+        return Err(Error::RequestNotOk(job_url.to_owned()));
+    }
+    let body = resp.text().await?;
+    parse_job_content(&body)
+}
+
+/// scrape the raw data and the company reviews from the job posting page
+/// then convert it to a crate::Job
+async fn convert(client: &Client, mut job: Job) -> crate::Job {
+    let raw_data = match scrape_job_content(client, &job.link).await {
+        Ok(content) => {
+            log::debug!("scraped job content for {}", job.link);
+            if content.rating.is_some() {
+                job.company.rating = content.rating;
+            }
+            job.description = Some(content.description);
+            Some(content.raw_data)
+        }
+        Err(e) => {
+            log::error!(
+                "failed to scrape job content for url: {}, error: {}",
+                job.link,
+                e
+            );
+            client.extraction_failed();
+            None
+        }
+    };
+    crate::Job::Glassdoor {
+        job: Box::new(job),
+        raw_data,
+    }
+}
+
+/// Scrape all jobs for given queries including the review summary of the employer,
+/// postings in `known_ids` were stored before and are skipped
+pub async fn scrape(
+    queries: Vec<SearchQuery>,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = crate::Job> {
+    let client = Client::builder(reqwest::Client::new())
+        .profile(headers::random())
+        .default_rate_limit(RATE_LIMIT)
+        .build();
+    let mut scraped_ids: HashSet<String> = known_ids;
+    async_stream::stream! {
+        for query in queries {
+            for page in 1..=MAX_PAGES {
+                let jobs = match scrape_search_page(&client, &query, page).await {
+                    Ok(jobs) => jobs,
+                    Err(e) => {
+                        log::error!("Stopping search for query: {}, error: {}", query.keywords, e);
+                        break;
+                    }
+                };
+                if jobs.is_empty() {
+                    log::info!("No more results for query: {} after page: {}", query.keywords, page - 1);
+                    break;
+                }
+                for job in jobs {
+                    if !scraped_ids.insert(job.glassdoor_id.clone()) {
+                        continue;
+                    }
+                    yield convert(&client, job).await;
+                }
+            }
+        }
+    }
+}
+
+pub struct GlassdoorSource;
+
+#[async_trait]
+impl JobSource for GlassdoorSource {
+    fn name(&self) -> &'static str {
+        "glassdoor"
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        scrape(params.queries, params.known_ids)
+            .await
+            .map(Ok)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("4,1"), Some(4.1));
+        assert_eq!(parse_number("3.8 ★"), Some(3.8));
+        assert_eq!(parse_number("1.234 Bewertungen"), Some(1234.0));
+        assert_eq!(parse_number("87 %"), Some(87.0));
+        assert_eq!(parse_number("keine"), None);
+    }
+
+    #[test]
+    fn test_parse_job_content_with_reviews() {
+        let body = r#"
+            <html><body>
+            <div class="jobDescriptionContent"><p>We build things.</p><p>In Rust.</p></div>
+            <div data-test="employer-rating">4,2</div>
+            <div data-test="employer-review-count">1.024 Bewertungen</div>
+            <div data-test="recommend-to-friend">81 %</div>
+            </body></html>
+        "#;
+        let content = parse_job_content(body).expect("Failed to parse html");
+        assert_eq!(content.raw_data, "We build things. In Rust.");
+        assert_eq!(content.description, "We build things.\n\nIn Rust.");
+        let rating = content.rating.expect("Rating should be present");
+        assert_eq!(rating.rating_average, Some(4.2));
+        assert_eq!(rating.rating_count, Some(1024));
+        assert_eq!(rating.recommend_to_friend, Some(81.0));
+        assert_eq!(rating.ceo_approval, None);
+    }
+}
@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ats::{self, Ats};
+use crate::client::Client;
+use crate::markdown;
+use crate::source::{base_url, env_list, JobSource, JobStream, SearchParams};
+
+type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Request error: '{0}'")]
+    Request(#[from] reqwest::Error),
+    #[error("Failed to scrape data from: '{0}'")]
+    RequestNotOk(String),
+}
+
+/// Public job board api, `GREENHOUSE_BASE_URL` points the scraper somewhere else
+pub const BASE_URL: &str = "https://boards-api.greenhouse.io";
+/// Board tokens of the companies to scrape, separated by `,`
+pub const BOARDS_ENV: &str = "GREENHOUSE_BOARDS";
+
+#[derive(Deserialize)]
+struct Board {
+    jobs: Vec<Posting>,
+}
+
+#[derive(Deserialize)]
+struct Name {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Posting {
+    id: u64,
+    title: String,
+    absolute_url: String,
+    updated_at: Option<DateTime<Utc>>,
+    location: Option<Name>,
+    /// Html of the description, escaped once more
+    content: Option<String>,
+    #[serde(default)]
+    departments: Vec<Name>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Job {
+    pub greenhouse_id: String,
+    /// Token of the company's board, e.g. "acme" for boards.greenhouse.io/acme
+    pub board: String,
+    pub title: String,
+    pub location: Option<String>,
+    pub departments: Vec<String>,
+    pub link: String,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Description as Markdown
+    pub description: Option<String>,
+}
+
+/// Greenhouse escapes the markup of the description, the text of the fragment is the html
+fn unescape(content: &str) -> String {
+    Html::parse_fragment(content)
+        .root_element()
+        .text()
+        .collect()
+}
+
+fn convert(board: &str, posting: Posting) -> Job {
+    Job {
+        greenhouse_id: posting.id.to_string(),
+        board: board.to_owned(),
+        title: posting.title,
+        location: posting.location.map(|location| location.name),
+        departments: posting
+            .departments
+            .into_iter()
+            .map(|department| department.name)
+            .collect(),
+        link: posting.absolute_url,
+        updated_at: posting.updated_at,
+        description: posting
+            .content
+            .map(|content| markdown::from_html(&unescape(&content))),
+    }
+}
+
+async fn scrape_board(client: &Client, base_url: &str, board: &str) -> Result<Vec<Job>> {
+    let url = format!("{}/v1/boards/{}/jobs?content=true", base_url, board);
+    log::debug!("requesting jobs of greenhouse board: {}", board);
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Err(Error::RequestNotOk(url));
+    }
+    let board_jobs = resp.json::<Board>().await?;
+    Ok(board_jobs
+        .jobs
+        .into_iter()
+        .map(|posting| convert(board, posting))
+        .collect())
+}
+
+#[derive(Clone)]
+pub struct GreenhouseSource {
+    pub boards: Vec<String>,
+    pub base_url: String,
+}
+
+impl Default for GreenhouseSource {
+    fn default() -> Self {
+        Self {
+            boards: env_list(BOARDS_ENV),
+            base_url: base_url("GREENHOUSE_BASE_URL", BASE_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl Ats for GreenhouseSource {
+    const NAME: &'static str = "greenhouse";
+    const CONFIG: &'static str = "GREENHOUSE_BOARDS, board tokens of companies";
+    type Error = Error;
+
+    fn companies(&self) -> &[String] {
+        &self.boards
+    }
+
+    async fn company_jobs(&self, client: &Client, board: &str) -> Result<Vec<crate::Job>> {
+        let jobs = scrape_board(client, &self.base_url, board).await?;
+        Ok(jobs
+            .into_iter()
+            .map(|job| crate::Job::Greenhouse { job: Box::new(job) })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl JobSource for GreenhouseSource {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        ats::jobs(self, params.known_ids)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::StreamExt;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_convert_unescapes_the_description() {
+        let posting = serde_json::from_str::<Posting>(
+            r#"{"id": 4012345, "title": "Backend Engineer",
+            "absolute_url": "https://boards.greenhouse.io/acme/jobs/4012345",
+            "updated_at": "2023-05-02T10:15:00-04:00",
+            "location": {"name": "Berlin, Germany"},
+            "content": "&lt;p&gt;We use &lt;strong&gt;Rust&lt;/strong&gt; &amp;amp; Go&lt;/p&gt;",
+            "departments": [{"id": 1, "name": "Engineering"}]}"#,
+        )
+        .unwrap();
+        let job = convert("acme", posting);
+        assert_eq!(job.greenhouse_id, "4012345");
+        assert_eq!(job.location.as_deref(), Some("Berlin, Germany"));
+        assert_eq!(job.departments, vec!["Engineering"]);
+        assert_eq!(job.description.as_deref(), Some("We use **Rust** & Go"));
+        assert_eq!(
+            job.updated_at.unwrap().to_rfc3339(),
+            "2023-05-02T14:15:00+00:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_boards_from_mock() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 3,
+            ..mock_server::Config::default()
+        })
+        .await;
+        let client = Client::builder(reqwest::Client::new()).build();
+        let source = GreenhouseSource {
+            boards: vec!["acme".to_owned(), "mock-gmbh".to_owned()],
+            base_url: server.greenhouse_url(),
+        };
+        let known = HashSet::from(["4004001".to_owned()]);
+        let jobs = ats::scrape(source, client, known).collect::<Vec<_>>().await;
+        let ids = jobs.iter().map(crate::Job::site_id).collect::<Vec<_>>();
+        assert_eq!(ids.len(), 5);
+        assert!(!ids.contains(&"4004001".to_owned()));
+    }
+}
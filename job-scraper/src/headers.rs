@@ -0,0 +1,181 @@
+use rand::seq::SliceRandom;
+use reqwest::header::{
+    HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, CONNECTION, UPGRADE_INSECURE_REQUESTS,
+    USER_AGENT,
+};
+
+/// What the browser is loading, page loads and `fetch` calls send different headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Destination {
+    /// Navigating to a html page
+    #[default]
+    Document,
+    /// Api call made by the javascript of a page on the same site
+    Fetch,
+}
+
+/// Headers of a single browser, sending all of them keeps the fingerprint consistent
+#[derive(Debug, PartialEq, Eq)]
+pub struct Profile {
+    pub name: &'static str,
+    user_agent: &'static str,
+    /// `Accept` of page loads
+    accept: &'static str,
+    accept_language: &'static str,
+    /// `sec-ch-ua` and `sec-ch-ua-platform`, only chromium based browsers send client hints
+    client_hints: Option<(&'static str, &'static str)>,
+    /// Safari leaves out `sec-fetch-user`
+    sec_fetch_user: bool,
+}
+
+const CHROMIUM_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.9";
+const FIREFOX_ACCEPT: &str =
+    "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8";
+const FETCH_ACCEPT: &str = "application/json, text/plain, */*";
+
+pub const PROFILES: [Profile; 6] = [
+    Profile {
+        name: "chrome-linux",
+        user_agent: "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/112.0.0.0 Safari/537.36",
+        accept: CHROMIUM_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+        client_hints: Some((
+            r#""Chromium";v="112", "Google Chrome";v="112", "Not:A-Brand";v="99""#,
+            r#""Linux""#,
+        )),
+        sec_fetch_user: true,
+    },
+    Profile {
+        name: "chrome-windows",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/113.0.0.0 Safari/537.36",
+        accept: CHROMIUM_ACCEPT,
+        accept_language: "de-DE,de;q=0.9,en-US;q=0.8,en;q=0.7",
+        client_hints: Some((
+            r#""Google Chrome";v="113", "Chromium";v="113", "Not-A.Brand";v="24""#,
+            r#""Windows""#,
+        )),
+        sec_fetch_user: true,
+    },
+    Profile {
+        name: "edge-windows",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/113.0.0.0 Safari/537.36 Edg/113.0.1774.42",
+        accept: CHROMIUM_ACCEPT,
+        accept_language: "de,de-DE;q=0.9,en;q=0.8,en-GB;q=0.7,en-US;q=0.6",
+        client_hints: Some((
+            r#""Microsoft Edge";v="113", "Chromium";v="113", "Not-A.Brand";v="24""#,
+            r#""Windows""#,
+        )),
+        sec_fetch_user: true,
+    },
+    Profile {
+        name: "firefox-linux",
+        user_agent: "Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/113.0",
+        accept: FIREFOX_ACCEPT,
+        accept_language: "en-US,en;q=0.5",
+        client_hints: None,
+        sec_fetch_user: true,
+    },
+    Profile {
+        name: "firefox-windows",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/113.0",
+        accept: FIREFOX_ACCEPT,
+        accept_language: "de,en-US;q=0.7,en;q=0.3",
+        client_hints: None,
+        sec_fetch_user: true,
+    },
+    Profile {
+        name: "safari-macos",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.4 Safari/605.1.15",
+        accept: "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        accept_language: "de-DE,de;q=0.9",
+        client_hints: None,
+        sec_fetch_user: false,
+    },
+];
+
+impl Profile {
+    pub fn headers(&self, destination: Destination) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static(self.user_agent));
+        headers.insert(
+            ACCEPT_LANGUAGE,
+            HeaderValue::from_static(self.accept_language),
+        );
+        headers.insert(CONNECTION, HeaderValue::from_static("keep-alive"));
+        if let Some((sec_ch_ua, platform)) = self.client_hints {
+            headers.insert("sec-ch-ua", HeaderValue::from_static(sec_ch_ua));
+            headers.insert("sec-ch-ua-mobile", HeaderValue::from_static("?0"));
+            headers.insert("sec-ch-ua-platform", HeaderValue::from_static(platform));
+        }
+        match destination {
+            Destination::Document => {
+                headers.insert(ACCEPT, HeaderValue::from_static(self.accept));
+                headers.insert("sec-fetch-dest", HeaderValue::from_static("document"));
+                headers.insert("sec-fetch-mode", HeaderValue::from_static("navigate"));
+                headers.insert("sec-fetch-site", HeaderValue::from_static("none"));
+                if self.sec_fetch_user {
+                    headers.insert("sec-fetch-user", HeaderValue::from_static("?1"));
+                }
+                headers.insert(UPGRADE_INSECURE_REQUESTS, HeaderValue::from_static("1"));
+            }
+            Destination::Fetch => {
+                headers.insert(ACCEPT, HeaderValue::from_static(FETCH_ACCEPT));
+                headers.insert("sec-fetch-dest", HeaderValue::from_static("empty"));
+                headers.insert("sec-fetch-mode", HeaderValue::from_static("cors"));
+                headers.insert("sec-fetch-site", HeaderValue::from_static("same-origin"));
+            }
+        }
+        headers
+    }
+}
+
+/// Profile for a new scraping session
+pub fn random() -> &'static Profile {
+    let profile = PROFILES.choose(&mut rand::thread_rng()).unwrap();
+    log::debug!("using browser profile {}", profile.name);
+    profile
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_profiles_are_consistent() {
+        for profile in PROFILES.iter() {
+            let headers = profile.headers(Destination::Document);
+            let user_agent = headers[USER_AGENT].to_str().unwrap();
+            let chromium = user_agent.contains("Chrome/");
+            assert_eq!(
+                headers.contains_key("sec-ch-ua"),
+                chromium,
+                "{}",
+                profile.name
+            );
+            if let Some(platform) = headers.get("sec-ch-ua-platform") {
+                let platform = platform.to_str().unwrap().trim_matches('"');
+                assert!(user_agent.contains(platform), "{}", profile.name);
+                let version = user_agent.split("Chrome/").nth(1).unwrap();
+                let major = version.split('.').next().unwrap();
+                let sec_ch_ua = headers["sec-ch-ua"].to_str().unwrap();
+                assert!(
+                    sec_ch_ua.contains(&format!("\"Chromium\";v=\"{}\"", major)),
+                    "{}",
+                    profile.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fetch_headers() {
+        let profile = &PROFILES[0];
+        let document = profile.headers(Destination::Document);
+        let fetch = profile.headers(Destination::Fetch);
+        assert_eq!(document[USER_AGENT], fetch[USER_AGENT]);
+        assert_eq!(fetch["sec-fetch-mode"], "cors");
+        assert_eq!(fetch[ACCEPT], FETCH_ACCEPT);
+        assert!(!fetch.contains_key("sec-fetch-user"));
+        assert!(!fetch.contains_key(UPGRADE_INSECURE_REQUESTS));
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::client::{Client, RateLimit};
+use crate::headers;
+use crate::query::{url_with_params, EmploymentType, SearchQuery};
+use crate::source::{base_url, JobSource, JobStream, SearchParams};
+
+type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Request error: '{0}'")]
+    Request(#[from] reqwest::Error),
+    #[error("Failed to scrape data from: '{0}'")]
+    RequestNotOk(String),
+    #[error("Content not found in html: '{0}'")]
+    ContentNotFound(&'static str),
+}
+
+/// German site, `INDEED_BASE_URL` points the scraper somewhere else
+pub const BASE_URL: &str = "https://de.indeed.com";
+const RESULTS_PER_PAGE: u32 = 10;
+/// Indeed doesn't serve results beyond an offset of 1000
+const MAX_OFFSET: u32 = 1000;
+const REMOTE_FILTER_ID: &str = "032b3046-06a3-4876-8dfd-474eb5e7ed11";
+
+/// Indeed answers bursts with a captcha page, stay close to one request per second
+const RATE_LIMIT: RateLimit = RateLimit {
+    requests_per_second: 1.0,
+    burst: 2,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Job {
+    pub indeed_id: String,
+    pub title: Option<String>,
+    pub company: Option<String>,
+    location: Option<String>,
+    salary: Option<String>,
+}
+
+/// Data only present on the detail page of a posting
+struct JobDetails {
+    title: Option<String>,
+    company: Option<String>,
+    location: Option<String>,
+    salary: Option<String>,
+    description: String,
+}
+
+fn job_search_url(base_url: &str, query: &SearchQuery, offset: u32) -> String {
+    let mut params = vec![
+        ("q", query.keywords.clone()),
+        ("l", query.location.clone().unwrap_or_default()),
+        ("start", offset.to_string()),
+    ];
+    if let Some(radius) = query.radius {
+        params.push(("radius", radius.to_string()));
+    }
+    if let Some(days) = query.recency.and_then(|recency| recency.days()) {
+        params.push(("fromage", days.to_string()));
+    }
+    if let Some(employment_type) = query.employment_type {
+        let job_type = match employment_type {
+            EmploymentType::FullTime => "fulltime",
+            EmploymentType::PartTime => "parttime",
+            EmploymentType::Freelance => "contract",
+            EmploymentType::Internship => "internship",
+            EmploymentType::Temporary => "temporary",
+        };
+        params.push(("jt", job_type.to_owned()));
+    }
+    if query.remote {
+        params.push(("remotejob", REMOTE_FILTER_ID.to_owned()));
+    }
+    url_with_params(&format!("{}/jobs", base_url), params)
+}
+
+fn job_url(base_url: &str, id: &str) -> String {
+    format!("{}/viewjob?jk={}", base_url, id)
+}
+
+fn select_text(el: ElementRef, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    el.select(&selector)
+        .next()
+        .map(|el| {
+            el.text()
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|text| !text.is_empty())
+}
+
+fn parse_search_page(body: &str) -> Vec<Job> {
+    let doc = Html::parse_document(body);
+    let item_selector = Selector::parse("div.job_seen_beacon").unwrap();
+    let id_selector = Selector::parse("a[data-jk]").unwrap();
+    doc.select(&item_selector)
+        .filter_map(|item| {
+            let indeed_id = item
+                .select(&id_selector)
+                .next()?
+                .value()
+                .attr("data-jk")?
+                .to_owned();
+            Some(Job {
+                indeed_id,
+                title: select_text(item, "h2.jobTitle span[title]"),
+                company: select_text(item, r#"[data-testid="company-name"]"#),
+                location: select_text(item, r#"[data-testid="text-location"]"#),
+                salary: select_text(item, ".salary-snippet-container"),
+            })
+        })
+        .collect()
+}
+
+fn parse_job_page(body: &str) -> Result<JobDetails> {
+    let doc = Html::parse_document(body);
+    let root = doc.root_element();
+    let description = select_text(root, "#jobDescriptionText")
+        .ok_or(Error::ContentNotFound("Job description"))?;
+    Ok(JobDetails {
+        title: select_text(root, "h1.jobsearch-JobInfoHeader-title"),
+        company: select_text(root, r#"[data-testid="inlineHeader-companyName"]"#),
+        location: select_text(root, r#"[data-testid="inlineHeader-companyLocation"]"#),
+        salary: select_text(root, "#salaryInfoAndJobType"),
+        description,
+    })
+}
+
+async fn scrape_search_page(
+    client: &Client,
+    base_url: &str,
+    query: &SearchQuery,
+    offset: u32,
+) -> Result<Vec<Job>> {
+    let url = job_search_url(base_url, query, offset);
+    log::debug!(
+        "requesting jobs from indeed, offset: {}, search: {}, location: {}",
+        offset,
+        query.keywords,
+        query.location.as_deref().unwrap_or_default()
+    );
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        log::error!(
+            "failed to retrieve results for offset: {}, search: {}, status: {}",
+            offset,
+            query.keywords,
+            resp.status()
+        );
+        return Err(Error::RequestNotOk(url));
+    }
+    let body = resp.text().await?;
+    Ok(parse_search_page(&body))
+}
+
+async fn scrape_job_page(client: &Client, base_url: &str, id: &str) -> Result<JobDetails> {
+    let url = job_url(base_url, id);
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Err(Error::RequestNotOk(url));
+    }
+    let body = resp.text().await?;
+    parse_job_page(&body)
+}
+
+/// fetch the detail page of the posting, fields found on the detail page
+/// take precedence over the ones from the search result card
+async fn convert(client: &Client, base_url: &str, mut job: Job) -> crate::Job {
+    let raw_data = match scrape_job_page(client, base_url, &job.indeed_id).await {
+        Ok(details) => {
+            log::debug!("scraped job content for {}", job.indeed_id);
+            job.title = details.title.or(job.title);
+            job.company = details.company.or(job.company);
+            job.location = details.location.or(job.location);
+            job.salary = details.salary.or(job.salary);
+            Some(details.description)
+        }
+        Err(e) => {
+            log::error!(
+                "failed to scrape job content for id: {}, error: {}",
+                job.indeed_id,
+                e
+            );
+            client.extraction_failed();
+            None
+        }
+    };
+    crate::Job::Indeed {
+        job: Box::new(job),
+        raw_data,
+    }
+}
+
+/// Scrape all jobs for the given queries except the already known ones
+pub async fn scrape(
+    base_url: String,
+    queries: Vec<SearchQuery>,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = crate::Job> {
+    let client = Client::builder(reqwest::Client::new())
+        .profile(headers::random())
+        .default_rate_limit(RATE_LIMIT)
+        .build();
+    scrape_with(client, base_url, queries, known_ids)
+}
+
+fn scrape_with(
+    client: Client,
+    base_url: String,
+    queries: Vec<SearchQuery>,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = crate::Job> {
+    let mut scraped_ids: HashSet<String> = known_ids;
+    async_stream::stream! {
+        for query in queries {
+            let mut offset = 0;
+            let mut previous_ids: Vec<String> = Vec::new();
+            while offset < MAX_OFFSET {
+                let jobs = match scrape_search_page(&client, &base_url, &query, offset).await {
+                    Ok(jobs) => jobs,
+                    Err(e) => {
+                        log::error!("Stopping search for query: {}, error: {}", query.keywords, e);
+                        break;
+                    }
+                };
+                // indeed repeats the last page for offsets past the end of the results
+                let page_ids = jobs.iter().map(|job| job.indeed_id.clone()).collect::<Vec<_>>();
+                if page_ids.is_empty() || page_ids == previous_ids {
+                    log::info!("No more results for query: {}", query.keywords);
+                    break;
+                }
+                previous_ids = page_ids;
+                // postings of earlier queries and stored ones are skipped without ending the search,
+                // queries of the same keywords in different locations overlap
+                for job in jobs {
+                    if !scraped_ids.insert(job.indeed_id.clone()) {
+                        continue;
+                    }
+                    yield convert(&client, &base_url, job).await;
+                }
+                offset += RESULTS_PER_PAGE;
+            }
+        }
+    }
+}
+
+pub struct IndeedSource {
+    pub base_url: String,
+}
+
+impl Default for IndeedSource {
+    fn default() -> Self {
+        Self {
+            base_url: base_url("INDEED_BASE_URL", BASE_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl JobSource for IndeedSource {
+    fn name(&self) -> &'static str {
+        "indeed"
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        scrape(self.base_url.clone(), params.queries, params.known_ids)
+            .await
+            .map(Ok)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_page() {
+        let body = r#"
+            <html><body>
+            <div class="job_seen_beacon">
+                <h2 class="jobTitle"><a data-jk="a1b2c3d4e5f6"><span title="Rust Engineer">Rust Engineer</span></a></h2>
+                <span data-testid="company-name">ACME GmbH</span>
+                <div data-testid="text-location">München</div>
+                <div class="salary-snippet-container">60.000 € – 80.000 € pro Jahr</div>
+            </div>
+            </body></html>
+        "#;
+        let jobs = parse_search_page(body);
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].indeed_id, "a1b2c3d4e5f6");
+        assert_eq!(jobs[0].title.as_deref(), Some("Rust Engineer"));
+        assert_eq!(jobs[0].company.as_deref(), Some("ACME GmbH"));
+        assert_eq!(
+            jobs[0].salary.as_deref(),
+            Some("60.000 € – 80.000 € pro Jahr")
+        );
+    }
+
+    #[test]
+    fn test_parse_job_page_without_description() {
+        let body = r#"<html><body><h1 class="jobsearch-JobInfoHeader-title">Rust Engineer</h1></body></html>"#;
+        assert!(parse_job_page(body).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_queries_are_paged_to_the_end() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 25,
+            ..mock_server::Config::default()
+        })
+        .await;
+        let client = Client::builder(reqwest::Client::new())
+            .default_rate_limit(RateLimit {
+                requests_per_second: 100.0,
+                burst: 10,
+            })
+            .build();
+        // every posting in Berlin is a posting in Germany as well
+        let queries = ["Berlin", "Germany"]
+            .into_iter()
+            .map(|location| SearchQuery {
+                location: Some(location.to_owned()),
+                ..SearchQuery::new("Rust")
+            })
+            .collect();
+        let known = HashSet::from(["00000000a1b2c3e6".to_owned()]);
+        let jobs = scrape_with(client, server.indeed_url(), queries, known)
+            .collect::<Vec<_>>()
+            .await;
+        let ids = jobs.iter().map(crate::Job::site_id).collect::<HashSet<_>>();
+        assert_eq!(jobs.len(), 24);
+        assert_eq!(ids.len(), 24);
+        assert_eq!(server.posting_requests(), 24);
+    }
+}
@@ -1,19 +1,119 @@
-use crate::cookies::combine_cookies;
+use crate::checkpoint::Checkpoints;
+use crate::client::{Client, RateLimit};
+use crate::cookies::{is_session_expired, CookieJar};
+use crate::headers::{self, Destination};
+use crate::source::{base_url, JobSource, JobStream, SearchParams, SourceError};
 use async_stream::stream;
-use futures::{Future, Stream};
-use reqwest::{
-    header::{HeaderMap, HeaderValue, COOKIE},
-    Client,
-};
+use async_trait::async_trait;
+use futures::{Future, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display, time::Duration};
+use std::{fmt::Display, path::PathBuf, sync::Arc};
 use thiserror::Error;
-use tokio::{io::AsyncWriteExt, time::sleep};
+use tokio::io::AsyncWriteExt;
+
+/// Live site, `INSTAFFO_BASE_URL` points the scraper somewhere else
+pub const BASE_URL: &str = "https://app.instaffo.com";
+
+const SESSION_COOKIE: &str = "_instaffo_session";
+/// Copy of the session cookie of a logged in browser, only needed to start a new session
+const SESSION_ENV: &str = "INSTAFFO_SESSION";
+const EMAIL_ENV: &str = "INSTAFFO_EMAIL";
+const PASSWORD_ENV: &str = "INSTAFFO_PASSWORD";
+/// Sign in of the candidate app, answers with a new session cookie.
+/// Instaffo runs on Rails (hence `_instaffo_session`), the path and the
+/// `{"user": {"email", "password"}}` body are the defaults of its Devise JSON sign in.
+/// The contract is not recorded from the live site: should it require a CSRF token
+/// the login fails with `LoginFailed`, a browser session in `INSTAFFO_SESSION` still works
+const LOGIN_PATH: &str = "/users/sign_in";
+
+/// Candidate account the scraper logs in with
+#[derive(Clone)]
+pub struct Credentials {
+    pub email: String,
+    pub password: String,
+}
+
+impl Credentials {
+    /// Reads `INSTAFFO_EMAIL` and `INSTAFFO_PASSWORD`, `None` unless both are set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            email: std::env::var(EMAIL_ENV).ok()?,
+            password: std::env::var(PASSWORD_ENV).ok()?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct LoginBody<'a> {
+    user: LoginUser<'a>,
+}
+
+#[derive(Serialize)]
+struct LoginUser<'a> {
+    email: &'a str,
+    password: &'a str,
+}
 
 #[derive(Debug, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 struct Filter {
     job_status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    salary_min: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    seniorities: Vec<String>,
+}
+
+/// Filters for the job suggestions, the `include_*` flags are applied
+/// to the suggestions returned by instaffo
+#[derive(Debug, Clone)]
+pub struct Filters {
+    pub remote: Option<bool>,
+    /// Minimum yearly salary
+    pub salary_min: Option<u32>,
+    /// Location names as shown by instaffo, e.g. "Berlin"
+    pub locations: Vec<String>,
+    /// e.g. "junior", "professional", "senior"
+    pub seniorities: Vec<String>,
+    pub include_favorites: bool,
+    pub include_seen: bool,
+    pub include_hidden: bool,
+}
+
+impl Default for Filters {
+    fn default() -> Self {
+        Self {
+            remote: None,
+            salary_min: None,
+            locations: Vec::new(),
+            seniorities: Vec::new(),
+            include_favorites: true,
+            include_seen: true,
+            include_hidden: true,
+        }
+    }
+}
+
+impl Filters {
+    fn request_filter(&self) -> Filter {
+        Filter {
+            job_status: "open".to_owned(),
+            remote: self.remote,
+            salary_min: self.salary_min,
+            locations: self.locations.clone(),
+            seniorities: self.seniorities.clone(),
+        }
+    }
+
+    fn includes(&self, job: &Job) -> bool {
+        (self.include_favorites || !job.favorite)
+            && (self.include_seen || !job.seen)
+            && (self.include_hidden || !job.hidden)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -95,6 +195,13 @@ struct ResponseBody {
     meta: Meta,
 }
 
+/// Position in the suggestions, saved as checkpoint after every page
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    pit_id: String,
+    search_after: (String, String),
+}
+
 #[derive(Debug, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct RequestBody {
@@ -105,29 +212,82 @@ pub struct RequestBody {
     pit_id: Option<String>,
 }
 
-pub async fn scrape(session_cookie_value: String) -> impl Stream<Item = crate::Job> {
-    let mut cookies = HashMap::new();
-    let session_cookie = String::from(urlencoding::encode(&session_cookie_value));
-    cookies.insert("_instaffo_session".to_owned(), session_cookie);
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        COOKIE,
-        HeaderValue::from_str(&combine_cookies(cookies.into_iter())).unwrap(),
-    );
-    let client = Client::builder().default_headers(headers).build().unwrap();
+/// Logs in with the credentials, the client stores the new session in its cookie jar
+async fn login(
+    client: &Client,
+    base_url: &str,
+    credentials: &Credentials,
+) -> Result<(), SourceError> {
+    log::info!("Logging in to instaffo as {}", credentials.email);
+    let body = LoginBody {
+        user: LoginUser {
+            email: &credentials.email,
+            password: &credentials.password,
+        },
+    };
+    let resp = client
+        .post(format!("{}{}", base_url, LOGIN_PATH))
+        .destination(Destination::Fetch)
+        .json(&body)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        log::error!("Instaffo login failed with status {}", resp.status());
+        return Err(SourceError::LoginFailed("instaffo"));
+    }
+    Ok(())
+}
+
+/// Without a stored session the scraper logs in with the credentials first,
+/// it does so again whenever the session expires during the scrape
+pub async fn scrape(
+    base_url: String,
+    cookies: Arc<CookieJar>,
+    credentials: Option<Credentials>,
+    filters: Filters,
+    checkpoints: Checkpoints,
+) -> impl Stream<Item = Result<crate::Job, SourceError>> {
+    // one page every five seconds
+    let client = Client::builder(reqwest::Client::new())
+        .profile(headers::random())
+        .cookies(cookies)
+        .rate_limit("app.instaffo.com", RateLimit::per_second(0.2))
+        .build();
+    job_suggestions(client, base_url, credentials, filters, checkpoints)
+}
+
+/// Pages through the job suggestions until instaffo returns an empty page.
+/// A resumed run continues after the last page of the interrupted one
+fn job_suggestions(
+    client: Client,
+    base_url: String,
+    credentials: Option<Credentials>,
+    filters: Filters,
+    checkpoints: Checkpoints,
+) -> impl Stream<Item = Result<crate::Job, SourceError>> {
+    let url = format!("{}/candidate/api/v1/job_suggestions", base_url);
+    let search = serde_json::to_string(&filters.request_filter()).expect("Filter must serialize");
     let mut pit_id: Option<String> = None;
     let mut search_after: Option<(String, String)> = None;
+    // a session that expires right after logging in won't be fixed by logging in again
+    let mut logged_in = false;
     stream! {
+        // the point in time of a checkpoint expires, instaffo refuses the request then
+        let mut resumed = false;
+        if let Some(cursor) = checkpoints.resume::<Cursor>("instaffo", &search).await {
+            pit_id = Some(cursor.pit_id);
+            search_after = Some(cursor.search_after);
+            resumed = true;
+        }
         loop {
             let body = RequestBody {
-                filters: Filter {
-                    job_status: "open".to_owned(),
-                },
-                pit_id,
-                search_after,
+                filters: filters.request_filter(),
+                pit_id: pit_id.clone(),
+                search_after: search_after.clone(),
             };
             let resp = client
-                .post("https://app.instaffo.com/candidate/api/v1/job_suggestions")
+                .post(&url)
+                .destination(Destination::Fetch)
                 .json(&body)
                 .send()
                 .await;
@@ -139,6 +299,30 @@ pub async fn scrape(session_cookie_value: String) -> impl Stream<Item = crate::J
                     },
 
                 };
+            if is_session_expired(&resp) {
+                let credentials = match &credentials {
+                    Some(credentials) if !logged_in => credentials,
+                    _ => {
+                        yield Err(SourceError::SessionExpired("instaffo"));
+                        break;
+                    }
+                };
+                if let Err(e) = login(&client, &base_url, credentials).await {
+                    yield Err(e);
+                    break;
+                }
+                logged_in = true;
+                continue;
+            }
+            logged_in = false;
+            if resp.status() != 200 && resumed {
+                log::warn!("Checkpoint is no longer valid (Status {}), starting over", resp.status());
+                pit_id = None;
+                search_after = None;
+                resumed = false;
+                continue;
+            }
+            resumed = false;
             if resp.status() != 200 {
                 log::error!("Request not successful, status code: {}", resp.status());
                 log::error!("Request not successful, body: {}", resp.text().await.unwrap_or("empty".to_owned()));
@@ -161,15 +345,95 @@ pub async fn scrape(session_cookie_value: String) -> impl Stream<Item = crate::J
                         break;
                     },
                 };
-            pit_id = Some(resp_body.meta.pit_id);
-            search_after = Some(resp_body.meta.search_after);
+            if resp_body.job_suggestions.is_empty() {
+                log::info!("No more job suggestions, stopping the scrape");
+                checkpoints.finish("instaffo", &search).await;
+                break;
+            }
+            let cursor = Cursor {
+                pit_id: resp_body.meta.pit_id,
+                search_after: resp_body.meta.search_after,
+            };
+            pit_id = Some(cursor.pit_id.clone());
+            search_after = Some(cursor.search_after.clone());
             for job_entry in resp_body.job_suggestions {
-                yield crate::Job::Instaffo {
+                if !filters.includes(&job_entry) {
+                    continue;
+                }
+                yield Ok(crate::Job::Instaffo {
                     job: Box::new(job_entry),
-                };
+                });
             }
+            checkpoints.save("instaffo", &search, &cursor).await;
             log::info!("Successfully yielded all jobs from request, starting next loop iteration with pit_id: {:?}, search_after: {:?}", pit_id, search_after);
-            sleep(Duration::from_secs(5)).await;
+        }
+    }
+}
+
+pub struct InstaffoSource {
+    pub filters: Filters,
+    pub base_url: String,
+    /// Jar the session is kept in between runs, `SCRAPER_COOKIE_DIR/instaffo.json` by default
+    pub cookie_jar: PathBuf,
+    /// Session cookie of a logged in browser, taken from `INSTAFFO_SESSION` by default
+    pub session: Option<String>,
+    /// Account to log in with once there is no session left
+    pub credentials: Option<Credentials>,
+}
+
+impl Default for InstaffoSource {
+    fn default() -> Self {
+        Self {
+            filters: Filters::default(),
+            base_url: base_url("INSTAFFO_BASE_URL", BASE_URL),
+            cookie_jar: CookieJar::env_path("instaffo"),
+            session: std::env::var(SESSION_ENV).ok(),
+            credentials: Credentials::from_env(),
+        }
+    }
+}
+
+impl InstaffoSource {
+    /// Session of the last run from the cookie jar, a new one is started if `session` changed.
+    /// Without any session the credentials are needed to log in
+    fn session(&self) -> Result<Arc<CookieJar>, SourceError> {
+        let jar = CookieJar::open(&self.cookie_jar)?;
+        if let Some(session_cookie) = &self.session {
+            jar.seed(SESSION_COOKIE, session_cookie.trim());
+        }
+        if jar.get(SESSION_COOKIE).is_none() && self.credentials.is_none() {
+            return Err(SourceError::MissingConfig(
+                "instaffo login, set INSTAFFO_EMAIL and INSTAFFO_PASSWORD or INSTAFFO_SESSION",
+            ));
+        }
+        Ok(Arc::new(jar))
+    }
+}
+
+#[async_trait]
+impl JobSource for InstaffoSource {
+    fn name(&self) -> &'static str {
+        "instaffo"
+    }
+
+    /// The suggestions contain the whole posting, known ones are only left out of the results
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        let known_ids = params.known_ids;
+        match self.session() {
+            Ok(cookies) => scrape(
+                self.base_url.clone(),
+                cookies,
+                self.credentials.clone(),
+                self.filters.clone(),
+                params.checkpoints,
+            )
+            .await
+            .filter(move |job| {
+                let known = matches!(job, Ok(job) if known_ids.contains(&job.site_id()));
+                futures::future::ready(!known)
+            })
+            .boxed(),
+            Err(e) => futures::stream::once(async { Err(e) }).boxed(),
         }
     }
 }
@@ -177,10 +441,219 @@ pub async fn scrape(session_cookie_value: String) -> impl Stream<Item = crate::J
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::checkpoint::{CheckpointStore, MemoryCheckpoints};
+    use crate::fixtures::{self, Fixtures};
     use reqwest::{
         header::{HeaderMap, HeaderValue, COOKIE},
         Client,
     };
     use serde_json::{json, Value};
     use std::collections::HashMap;
+
+    #[test]
+    fn test_request_body_with_filters() {
+        let filters = Filters {
+            remote: Some(true),
+            salary_min: Some(60000),
+            seniorities: vec!["senior".to_owned()],
+            ..Default::default()
+        };
+        let body = RequestBody {
+            filters: filters.request_filter(),
+            ..Default::default()
+        };
+        let body: Value = serde_json::to_value(body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "filters": {
+                    "jobStatus": "open",
+                    "remote": true,
+                    "salaryMin": 60000,
+                    "seniorities": ["senior"],
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_job_suggestions() {
+        // only needed to record the fixture, the jar stays out of the repo
+        let cookies =
+            CookieJar::open(std::env::temp_dir().join("instaffo-fixture-cookies.json")).unwrap();
+        cookies.seed(
+            SESSION_COOKIE,
+            &std::env::var(SESSION_ENV).unwrap_or_default(),
+        );
+        let client = crate::client::Client::builder(reqwest::Client::new())
+            .cookies(Arc::new(cookies))
+            .fixtures(Fixtures::from_env(fixtures::path(
+                "instaffo_job_suggestions",
+            )))
+            .build();
+        let filters = Filters {
+            include_seen: false,
+            ..Default::default()
+        };
+        let jobs = job_suggestions(
+            client,
+            BASE_URL.to_owned(),
+            None,
+            filters,
+            Checkpoints::default(),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(jobs.len(), 1);
+        let Ok(crate::Job::Instaffo { job }) = &jobs[0] else {
+            panic!("Expected an instaffo job");
+        };
+        assert_eq!(job.job.name, "Senior Rust Engineer");
+        assert_eq!(job.job.salary_min, Some(75000));
+    }
+
+    #[tokio::test]
+    async fn test_expired_session() {
+        let server = mock_server::MockServer::start(mock_server::Config::default()).await;
+        let path =
+            std::env::temp_dir().join(format!("instaffo-expired-{}.json", std::process::id()));
+        let cookies = CookieJar::open(path).unwrap();
+        cookies.seed(SESSION_COOKIE, mock_server::EXPIRED_SESSION);
+        let jobs = scrape(
+            server.instaffo_url(),
+            Arc::new(cookies),
+            None,
+            Filters::default(),
+            Checkpoints::default(),
+        )
+        .await
+        .collect::<Vec<_>>()
+        .await;
+        assert!(matches!(
+            jobs[..],
+            [Err(SourceError::SessionExpired("instaffo"))]
+        ));
+    }
+
+    fn mock_client(name: &str) -> crate::client::Client {
+        let path = std::env::temp_dir().join(format!("{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        crate::client::Client::builder(reqwest::Client::new())
+            .default_rate_limit(RateLimit {
+                requests_per_second: 100.0,
+                burst: 10,
+            })
+            .cookies(Arc::new(CookieJar::open(path).unwrap()))
+            .build()
+    }
+
+    fn credentials(password: &str) -> Option<Credentials> {
+        Some(Credentials {
+            email: "candidate@example.com".to_owned(),
+            password: password.to_owned(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_logs_in_again_when_session_expires() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 25,
+            expire_session_every: Some(2),
+            ..mock_server::Config::default()
+        })
+        .await;
+        let jobs = job_suggestions(
+            mock_client("instaffo-relogin"),
+            server.instaffo_url(),
+            credentials(mock_server::CANDIDATE_PASSWORD),
+            Filters::default(),
+            Checkpoints::default(),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(jobs.len(), 25);
+        assert!(jobs.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_password() {
+        let server = mock_server::MockServer::start(mock_server::Config::default()).await;
+        let jobs = job_suggestions(
+            mock_client("instaffo-wrong-password"),
+            server.instaffo_url(),
+            credentials("wrong"),
+            Filters::default(),
+            Checkpoints::default(),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        assert!(matches!(
+            jobs[..],
+            [Err(SourceError::LoginFailed("instaffo"))]
+        ));
+    }
+
+    fn uuid(job: &Result<crate::Job, SourceError>) -> String {
+        match job {
+            Ok(job) => job.site_id(),
+            Err(e) => panic!("Failed to scrape job: {}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 45,
+            ..mock_server::Config::default()
+        })
+        .await;
+        let store = Arc::new(MemoryCheckpoints::default());
+        let suggestions = |name: &str, resume: bool| {
+            job_suggestions(
+                mock_client(name),
+                server.instaffo_url(),
+                credentials(mock_server::CANDIDATE_PASSWORD),
+                Filters::default(),
+                Checkpoints::new(store.clone(), resume),
+            )
+        };
+        // interrupted on the second page
+        let jobs = suggestions("instaffo-interrupted", false)
+            .take(25)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(jobs.len(), 25);
+        assert_eq!(store.len(), 1);
+
+        let jobs = suggestions("instaffo-resumed", true)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(jobs.len(), 25);
+        assert_eq!(uuid(&jobs[0]), "00000000-0000-4000-8000-000000000020");
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired_checkpoint_starts_over() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 30,
+            ..mock_server::Config::default()
+        })
+        .await;
+        let store = Arc::new(MemoryCheckpoints::default());
+        let search = serde_json::to_string(&Filters::default().request_filter()).unwrap();
+        let cursor = json!({"pit_id": "expired", "search_after": ["20", "x"]});
+        store.save("instaffo", &search, cursor).await;
+        let jobs = job_suggestions(
+            mock_client("instaffo-expired-checkpoint"),
+            server.instaffo_url(),
+            credentials(mock_server::CANDIDATE_PASSWORD),
+            Filters::default(),
+            Checkpoints::new(store.clone(), true),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(jobs.len(), 30);
+        assert_eq!(uuid(&jobs[0]), "00000000-0000-4000-8000-000000000000");
+    }
 }
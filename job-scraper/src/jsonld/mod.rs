@@ -0,0 +1,552 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use futures::{Stream, StreamExt};
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::Url;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::client::Client;
+use crate::headers;
+use crate::markdown;
+use crate::source::{env_list, JobSource, JobStream, SearchParams, SourceError};
+
+/// Career pages or sitemaps to start from, separated by `,`
+pub const SEEDS_ENV: &str = "JSONLD_SEEDS";
+/// Pages crawled per domain, `DEFAULT_MAX_PAGES` if not set
+pub const MAX_PAGES_ENV: &str = "JSONLD_MAX_PAGES";
+pub const DEFAULT_MAX_PAGES: u32 = 200;
+
+/// Links to these are never pages with postings
+const SKIPPED_EXTENSIONS: [&str; 9] = [
+    ".pdf", ".jpg", ".jpeg", ".png", ".gif", ".svg", ".zip", ".css", ".js",
+];
+
+/// A schema.org `JobPosting` published by the employer itself
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Job {
+    /// `url` of the posting. Without one it's the page it was found on, with the `identifier`
+    /// or the position of the posting on the page as fragment
+    pub url: String,
+    pub title: Option<String>,
+    pub company: Option<String>,
+    /// Every location of the posting separated by `; `
+    pub location: Option<String>,
+    pub remote: bool,
+    /// Description as Markdown
+    pub description: Option<String>,
+    pub employment_type: Option<String>,
+    pub salary: Option<String>,
+    pub date_posted: Option<DateTime<Utc>>,
+    pub valid_through: Option<DateTime<Utc>>,
+    /// The `JobPosting` as published
+    pub json: Value,
+}
+
+/// Text of a value that might be a string, a `Thing` with a name or a list of either
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.trim().to_owned()).filter(|text| !text.is_empty()),
+        Value::Object(object) => object.get("name").and_then(text),
+        Value::Array(values) => {
+            let texts = values.iter().filter_map(text).collect::<Vec<_>>();
+            (!texts.is_empty()).then(|| texts.join(", "))
+        }
+        _ => None,
+    }
+}
+
+/// Dates are either full timestamps or plain days
+fn date(value: &Value) -> Option<DateTime<Utc>> {
+    let date = value.as_str()?.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Some(date.with_timezone(&Utc));
+    }
+    let day = NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()?;
+    Some(Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0)?))
+}
+
+fn location(posting: &Value) -> Option<String> {
+    let places = match &posting["jobLocation"] {
+        Value::Array(places) => places.iter().collect(),
+        place => vec![place],
+    };
+    let names = places
+        .into_iter()
+        .filter_map(|place| {
+            let address = &place["address"];
+            let parts = ["addressLocality", "addressRegion", "addressCountry"]
+                .iter()
+                .filter_map(|key| text(&address[*key]))
+                .collect::<Vec<_>>();
+            if parts.is_empty() {
+                text(address).or_else(|| text(place))
+            } else {
+                Some(parts.join(", "))
+            }
+        })
+        .collect::<Vec<_>>();
+    (!names.is_empty()).then(|| names.join("; "))
+}
+
+/// Salary of a `MonetaryAmount`, e.g. "45000 – 60000 EUR per year"
+pub(crate) fn salary_text(salary: &Value) -> Option<String> {
+    let amount = &salary["value"];
+    let value = |key: &str| match &amount[key] {
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(value) => Some(value.clone()),
+        _ => None,
+    };
+    let range = match (value("minValue"), value("maxValue"), value("value")) {
+        (Some(min), Some(max), _) => format!("{} – {}", min, max),
+        (Some(single), None, _) | (None, Some(single), _) | (None, None, Some(single)) => single,
+        _ => return None,
+    };
+    let mut text = range;
+    if let Some(currency) = salary["currency"].as_str() {
+        text = format!("{} {}", text, currency);
+    }
+    if let Some(unit) = amount["unitText"].as_str() {
+        text = format!("{} per {}", text, unit.to_lowercase());
+    }
+    Some(text)
+}
+
+pub(crate) fn is_posting(value: &Value) -> bool {
+    match &value["@type"] {
+        Value::String(kind) => kind == "JobPosting",
+        Value::Array(kinds) => kinds.iter().any(|kind| kind == "JobPosting"),
+        _ => false,
+    }
+}
+
+/// Collects the postings of a JSON-LD block, they might be nested in a `@graph` or a list
+fn find_postings<'a>(value: &'a Value, postings: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(_) if is_posting(value) => postings.push(value),
+        Value::Object(object) => object
+            .values()
+            .for_each(|value| find_postings(value, postings)),
+        Value::Array(values) => values
+            .iter()
+            .for_each(|value| find_postings(value, postings)),
+        _ => {}
+    }
+}
+
+/// `identifier` of a posting, plain text or a `PropertyValue`
+fn identifier(value: &Value) -> Option<String> {
+    match value {
+        Value::String(id) => Some(id.trim().to_owned()).filter(|id| !id.is_empty()),
+        Value::Number(id) => Some(id.to_string()),
+        Value::Object(object) => object.get("value").and_then(identifier),
+        _ => None,
+    }
+}
+
+/// `index` is the position of the posting on the page
+fn normalize(posting: &Value, page: &Url, index: usize) -> Job {
+    let url = match posting["url"].as_str().and_then(|url| page.join(url).ok()) {
+        Some(url) => url,
+        None => {
+            // a listing page can hold several postings without a url of their own
+            let id = identifier(&posting["identifier"]).unwrap_or_else(|| index.to_string());
+            let mut url = page.clone();
+            url.set_fragment(Some(&format!("posting-{}", id)));
+            url
+        }
+    };
+    Job {
+        url: url.to_string(),
+        title: text(&posting["title"]),
+        company: text(&posting["hiringOrganization"]),
+        location: location(posting),
+        remote: posting["jobLocationType"].as_str() == Some("TELECOMMUTE"),
+        description: posting["description"].as_str().map(markdown::from_html),
+        employment_type: text(&posting["employmentType"]),
+        salary: salary_text(&posting["baseSalary"]),
+        date_posted: date(&posting["datePosted"]),
+        valid_through: date(&posting["validThrough"]),
+        json: posting.clone(),
+    }
+}
+
+/// What a crawled page contributes, postings and the pages to visit next
+#[derive(Debug, Default)]
+struct Page {
+    postings: Vec<Job>,
+    links: Vec<Url>,
+}
+
+fn is_sitemap(body: &str) -> bool {
+    let end = (0..=body.len().min(500))
+        .rev()
+        .find(|end| body.is_char_boundary(*end))
+        .unwrap_or_default();
+    let start = &body[..end];
+    start.contains("<urlset") || start.contains("<sitemapindex")
+}
+
+/// Pages and nested sitemaps listed in a sitemap
+fn sitemap_urls(body: &str) -> Vec<String> {
+    lazy_static! {
+        static ref LOC: Regex = Regex::new(r"<loc>\s*(.*?)\s*</loc>").unwrap();
+    }
+    LOC.captures_iter(body)
+        .map(|captures| captures[1].replace("&amp;", "&"))
+        .collect()
+}
+
+fn is_crawlable(url: &Url) -> bool {
+    let path = url.path().to_lowercase();
+    matches!(url.scheme(), "http" | "https")
+        && !SKIPPED_EXTENSIONS
+            .iter()
+            .any(|extension| path.ends_with(extension))
+}
+
+fn parse_page(body: &str, url: &Url) -> Page {
+    if is_sitemap(body) {
+        let links = sitemap_urls(body)
+            .iter()
+            .filter_map(|loc| url.join(loc).ok())
+            .collect();
+        return Page {
+            postings: Vec::new(),
+            links: filter_links(links, url),
+        };
+    }
+    let doc = Html::parse_document(body);
+    let scripts = Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
+    let anchors = Selector::parse("a[href]").unwrap();
+    let blocks = doc
+        .select(&scripts)
+        .filter_map(|script| serde_json::from_str::<Value>(&script.text().collect::<String>()).ok())
+        .collect::<Vec<_>>();
+    let mut postings = Vec::new();
+    blocks
+        .iter()
+        .for_each(|block| find_postings(block, &mut postings));
+    let links = doc
+        .select(&anchors)
+        .filter_map(|anchor| url.join(anchor.value().attr("href")?).ok())
+        .collect();
+    Page {
+        postings: postings
+            .into_iter()
+            .enumerate()
+            .map(|(index, posting)| normalize(posting, url, index))
+            .collect(),
+        links: filter_links(links, url),
+    }
+}
+
+/// Links on the domain of the page without fragments, the same page isn't crawled twice
+fn filter_links(links: Vec<Url>, page: &Url) -> Vec<Url> {
+    links
+        .into_iter()
+        .filter(|link| link.host_str() == page.host_str() && is_crawlable(link))
+        .map(|mut link| {
+            link.set_fragment(None);
+            link
+        })
+        .collect()
+}
+
+/// Crawls the domains of the seeds, `max_pages` pages each, and returns every `JobPosting`
+/// found on the way. Pages of postings in `known_ids` aren't fetched again
+pub async fn scrape(
+    seeds: Vec<String>,
+    max_pages: u32,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = crate::Job> {
+    let client = Client::builder(reqwest::Client::new())
+        .profile(headers::random())
+        .build();
+    scrape_with(client, seeds, max_pages, known_ids)
+}
+
+fn scrape_with(
+    client: Client,
+    seeds: Vec<String>,
+    max_pages: u32,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = crate::Job> {
+    let mut queue = seeds
+        .iter()
+        .filter_map(|seed| match Url::parse(seed) {
+            Ok(url) => Some(url),
+            Err(e) => {
+                log::error!("Ignoring invalid seed '{}': {}", seed, e);
+                None
+            }
+        })
+        .collect::<VecDeque<_>>();
+    let mut visited = queue.iter().cloned().collect::<HashSet<_>>();
+    let mut found = known_ids;
+    let mut pages: HashMap<String, u32> = HashMap::new();
+    async_stream::stream! {
+        while let Some(url) = queue.pop_front() {
+            if found.contains(url.as_str()) {
+                continue;
+            }
+            let host = url.host_str().unwrap_or_default().to_owned();
+            let crawled = pages.entry(host.clone()).or_default();
+            if *crawled >= max_pages {
+                log::debug!("Page limit of {} reached, skipping {}", host, url);
+                continue;
+            }
+            *crawled += 1;
+            let resp = match client.get(url.clone()).send().await {
+                Ok(resp) if resp.status().is_success() => resp,
+                Ok(resp) => {
+                    log::warn!("Skipping {} (Status {})", url, resp.status());
+                    continue;
+                }
+                Err(e) => {
+                    log::error!("Failed to fetch {}: {}", url, e);
+                    continue;
+                }
+            };
+            let body = match resp.text().await {
+                Ok(body) => body,
+                Err(e) => {
+                    log::error!("Failed to read {}: {}", url, e);
+                    continue;
+                }
+            };
+            let page = parse_page(&body, &url);
+            for link in page.links {
+                if visited.insert(link.clone()) {
+                    queue.push_back(link);
+                }
+            }
+            for job in page.postings {
+                if found.insert(job.url.clone()) {
+                    yield crate::Job::Generic { job: Box::new(job) };
+                }
+            }
+        }
+    }
+}
+
+pub struct JsonLdSource {
+    pub seeds: Vec<String>,
+    pub max_pages: u32,
+}
+
+impl Default for JsonLdSource {
+    fn default() -> Self {
+        Self {
+            seeds: env_list(SEEDS_ENV),
+            max_pages: std::env::var(MAX_PAGES_ENV)
+                .ok()
+                .and_then(|max_pages| max_pages.parse().ok())
+                .unwrap_or(DEFAULT_MAX_PAGES),
+        }
+    }
+}
+
+#[async_trait]
+impl JobSource for JsonLdSource {
+    fn name(&self) -> &'static str {
+        "jsonld"
+    }
+
+    /// The career sites are crawled completely, the search queries don't apply to them
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        if self.seeds.is_empty() {
+            let error = SourceError::MissingConfig("JSONLD_SEEDS, career pages or sitemaps");
+            return futures::stream::once(async { Err(error) }).boxed();
+        }
+        scrape(self.seeds.clone(), self.max_pages, params.known_ids)
+            .await
+            .map(Ok)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::RateLimit;
+
+    #[test]
+    fn test_normalizes_postings_of_a_graph() {
+        let body = r#"<html><head><script type="application/ld+json">
+        {"@context": "https://schema.org", "@graph": [
+            {"@type": "Organization", "name": "Mock GmbH"},
+            {"@type": ["JobPosting"], "title": " Rust Engineer ", "url": "/careers/rust",
+             "description": "<p>Build <b>services</b></p><ul><li>tokio</li></ul>",
+             "hiringOrganization": {"@type": "Organization", "name": "Mock GmbH"},
+             "jobLocation": [
+                {"@type": "Place", "address": {"addressLocality": "Berlin", "addressCountry": "DE"}},
+                {"@type": "Place", "address": "Hamburg"}],
+             "jobLocationType": "TELECOMMUTE",
+             "employmentType": ["FULL_TIME", "PART_TIME"],
+             "datePosted": "2023-05-02",
+             "validThrough": "2023-06-30T23:59:00+02:00",
+             "baseSalary": {"currency": "EUR", "value": {"value": 70000, "unitText": "YEAR"}}}
+        ]}</script></head><body></body></html>"#;
+        let page = Url::parse("https://jobs.example.com/careers").unwrap();
+        let Page { postings, .. } = parse_page(body, &page);
+        assert_eq!(postings.len(), 1);
+        let job = &postings[0];
+        assert_eq!(job.url, "https://jobs.example.com/careers/rust");
+        assert_eq!(job.title.as_deref(), Some("Rust Engineer"));
+        assert_eq!(job.company.as_deref(), Some("Mock GmbH"));
+        assert_eq!(job.location.as_deref(), Some("Berlin, DE; Hamburg"));
+        assert!(job.remote);
+        assert_eq!(
+            job.description.as_deref(),
+            Some("Build **services**\n\n- tokio")
+        );
+        assert_eq!(job.employment_type.as_deref(), Some("FULL_TIME, PART_TIME"));
+        assert_eq!(job.salary.as_deref(), Some("70000 EUR per year"));
+        assert_eq!(
+            job.date_posted.unwrap().to_rfc3339(),
+            "2023-05-02T00:00:00+00:00"
+        );
+        assert_eq!(
+            job.valid_through.unwrap().to_rfc3339(),
+            "2023-06-30T21:59:00+00:00"
+        );
+        assert_eq!(job.json["title"], " Rust Engineer ");
+    }
+
+    #[test]
+    fn test_postings_without_url_on_one_page() {
+        let body = r#"<html><head>
+        <script type="application/ld+json">{"@type": "JobPosting", "title": "Rust Engineer",
+            "identifier": {"@type": "PropertyValue", "name": "Mock GmbH", "value": "R-17"}}</script>
+        <script type="application/ld+json">{"@type": "JobPosting", "title": "Go Engineer"}</script>
+        </head><body></body></html>"#;
+        let page = Url::parse("https://jobs.example.com/careers").unwrap();
+        let urls = parse_page(body, &page)
+            .postings
+            .into_iter()
+            .map(|job| job.url)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            vec![
+                "https://jobs.example.com/careers#posting-R-17",
+                "https://jobs.example.com/careers#posting-1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_links_stay_on_the_domain() {
+        let body = r##"<a href="/careers/1#apply">1</a><a href="https://other.example.com/careers/2">2</a>
+            <a href="mailto:jobs@example.com">mail</a><a href="/files/benefits.pdf">pdf</a>
+            <a href="https://jobs.example.com/careers/3">3</a>"##;
+        let page = Url::parse("https://jobs.example.com/careers").unwrap();
+        let links = parse_page(body, &page)
+            .links
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![
+                "https://jobs.example.com/careers/1",
+                "https://jobs.example.com/careers/3"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sitemap() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+            <url><loc>https://jobs.example.com/careers/1?lang=de&amp;ref=sitemap</loc></url>
+            <url><loc> https://jobs.example.com/careers/2 </loc></url>
+            <url><loc>https://other.example.com/careers/3</loc></url>
+        </urlset>"#;
+        let sitemap = Url::parse("https://jobs.example.com/sitemap.xml").unwrap();
+        let page = parse_page(body, &sitemap);
+        assert!(page.postings.is_empty());
+        assert_eq!(
+            page.links.iter().map(Url::as_str).collect::<Vec<_>>(),
+            vec![
+                "https://jobs.example.com/careers/1?lang=de&ref=sitemap",
+                "https://jobs.example.com/careers/2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_sitemap_with_umlauts_at_the_cut() {
+        let mut body = "<!-- Karriere bei Mock GmbH -->".to_owned();
+        body.push_str(&" ".repeat(499 - body.len()));
+        body.push_str("ü <urlset><url><loc>https://jobs.example.com/stellen</loc></url></urlset>");
+        assert!(!body.is_char_boundary(500));
+        assert!(!is_sitemap(&body));
+        let page = Url::parse("https://jobs.example.com/karriere").unwrap();
+        assert!(parse_page(&format!("<html>{}</html>", body), &page)
+            .postings
+            .is_empty());
+        assert!(is_sitemap(r#"<?xml version="1.0"?><urlset></urlset>"#));
+    }
+
+    fn mock_client() -> Client {
+        Client::builder(reqwest::Client::new())
+            .default_rate_limit(RateLimit {
+                requests_per_second: 100.0,
+                burst: 10,
+            })
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_crawls_career_site() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 10,
+            malformed_every: Some(5),
+            ..mock_server::Config::default()
+        })
+        .await;
+        let jobs = scrape_with(
+            mock_client(),
+            vec![server.careers_url()],
+            50,
+            HashSet::new(),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        // every fifth posting page has no markup
+        assert_eq!(jobs.len(), 8);
+
+        // the listing and two postings
+        let limited = scrape_with(mock_client(), vec![server.careers_url()], 3, HashSet::new())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(limited.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_crawls_sitemap_without_known_postings() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 4,
+            ..mock_server::Config::default()
+        })
+        .await;
+        let known = format!("{}/0", server.careers_url());
+        let jobs = scrape_with(
+            mock_client(),
+            vec![server.sitemap_url()],
+            50,
+            HashSet::from([known.clone()]),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        let urls = jobs.iter().map(crate::Job::site_id).collect::<Vec<_>>();
+        assert_eq!(urls.len(), 3);
+        assert!(!urls.contains(&known));
+    }
+}
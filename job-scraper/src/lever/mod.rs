@@ -0,0 +1,247 @@
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ats::{self, Ats};
+use crate::client::Client;
+use crate::markdown;
+use crate::source::{base_url, env_list, JobSource, JobStream, SearchParams};
+
+type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Request error: '{0}'")]
+    Request(#[from] reqwest::Error),
+    #[error("Failed to scrape data from: '{0}'")]
+    RequestNotOk(String),
+}
+
+/// Public postings api, `LEVER_BASE_URL` points the scraper somewhere else
+pub const BASE_URL: &str = "https://api.lever.co";
+/// Names of the companies to scrape as in jobs.lever.co/<company>, separated by `,`
+pub const COMPANIES_ENV: &str = "LEVER_COMPANIES";
+
+const PAGE_SIZE: usize = 100;
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Categories {
+    location: Option<String>,
+    team: Option<String>,
+    commitment: Option<String>,
+    #[serde(default)]
+    all_locations: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct List {
+    text: String,
+    /// `li` elements without the surrounding list
+    content: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Posting {
+    id: String,
+    text: String,
+    hosted_url: String,
+    /// Milliseconds since the epoch
+    created_at: Option<i64>,
+    #[serde(default)]
+    categories: Categories,
+    description: Option<String>,
+    #[serde(default)]
+    lists: Vec<List>,
+    additional: Option<String>,
+    workplace_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Job {
+    pub lever_id: String,
+    pub company: String,
+    pub title: String,
+    /// Every location of the posting, the first one is the main location
+    pub locations: Vec<String>,
+    pub team: Option<String>,
+    /// e.g. "Full-time"
+    pub commitment: Option<String>,
+    /// "onsite", "hybrid" or "remote"
+    pub workplace_type: Option<String>,
+    pub link: String,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Description with its lists of requirements and benefits as Markdown
+    pub description: Option<String>,
+}
+
+/// Lever splits the description into an intro, titled lists and a closing part
+fn description_html(posting: &Posting) -> Option<String> {
+    let mut html = posting.description.clone().unwrap_or_default();
+    for list in &posting.lists {
+        html.push_str(&format!("<h3>{}</h3><ul>{}</ul>", list.text, list.content));
+    }
+    html.push_str(posting.additional.as_deref().unwrap_or_default());
+    (!html.trim().is_empty()).then_some(html)
+}
+
+fn convert(company: &str, posting: Posting) -> Job {
+    let description = description_html(&posting).map(|html| markdown::from_html(&html));
+    let Categories {
+        location,
+        team,
+        commitment,
+        all_locations,
+    } = posting.categories;
+    let mut locations = location.into_iter().collect::<Vec<_>>();
+    for location in all_locations {
+        if !locations.contains(&location) {
+            locations.push(location);
+        }
+    }
+    Job {
+        lever_id: posting.id,
+        company: company.to_owned(),
+        title: posting.text,
+        locations,
+        team,
+        commitment,
+        workplace_type: posting.workplace_type,
+        link: posting.hosted_url,
+        created_at: posting
+            .created_at
+            .and_then(|millis| Utc.timestamp_millis_opt(millis).single()),
+        description,
+    }
+}
+
+async fn scrape_page(
+    client: &Client,
+    base_url: &str,
+    company: &str,
+    skip: usize,
+) -> Result<Vec<Posting>> {
+    let url = format!(
+        "{}/v0/postings/{}?mode=json&skip={}&limit={}",
+        base_url, company, skip, PAGE_SIZE
+    );
+    log::debug!("requesting lever postings of: {}, skip: {}", company, skip);
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Err(Error::RequestNotOk(url));
+    }
+    Ok(resp.json().await?)
+}
+
+#[derive(Clone)]
+pub struct LeverSource {
+    pub companies: Vec<String>,
+    pub base_url: String,
+}
+
+impl Default for LeverSource {
+    fn default() -> Self {
+        Self {
+            companies: env_list(COMPANIES_ENV),
+            base_url: base_url("LEVER_BASE_URL", BASE_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl Ats for LeverSource {
+    const NAME: &'static str = "lever";
+    const CONFIG: &'static str = "LEVER_COMPANIES, companies on jobs.lever.co";
+    type Error = Error;
+
+    fn companies(&self) -> &[String] {
+        &self.companies
+    }
+
+    /// Pages until a page isn't full, a failing page ends the company with the postings so far
+    async fn company_jobs(&self, client: &Client, company: &str) -> Result<Vec<crate::Job>> {
+        let mut jobs = Vec::new();
+        loop {
+            let postings = match scrape_page(client, &self.base_url, company, jobs.len()).await {
+                Ok(postings) => postings,
+                Err(e) if jobs.is_empty() => return Err(e),
+                Err(e) => {
+                    log::error!("Stopping lever company: {}, error: {}", company, e);
+                    break;
+                }
+            };
+            let count = postings.len();
+            jobs.extend(postings.into_iter().map(|posting| crate::Job::Lever {
+                job: Box::new(convert(company, posting)),
+            }));
+            if count < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(jobs)
+    }
+}
+
+#[async_trait]
+impl JobSource for LeverSource {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        ats::jobs(self, params.known_ids)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::StreamExt;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_convert_joins_the_description() {
+        let posting = serde_json::from_str::<Posting>(
+            r#"{"id": "5b2a-mock", "text": "Platform Engineer",
+            "hostedUrl": "https://jobs.lever.co/acme/5b2a-mock",
+            "createdAt": 1683014400000,
+            "categories": {"location": "Berlin", "team": "Platform", "commitment": "Full-time",
+                "allLocations": ["Berlin", "Remote Germany"]},
+            "description": "<div>We run Kubernetes.</div>",
+            "lists": [{"text": "Requirements", "content": "<li>Rust</li><li>Terraform</li>"}],
+            "additional": "<div>Apply now</div>",
+            "workplaceType": "hybrid"}"#,
+        )
+        .unwrap();
+        let job = convert("acme", posting);
+        assert_eq!(job.locations, vec!["Berlin", "Remote Germany"]);
+        assert_eq!(
+            job.created_at.unwrap().to_rfc3339(),
+            "2023-05-02T08:00:00+00:00"
+        );
+        assert_eq!(
+            job.description.as_deref(),
+            Some("We run Kubernetes.\n\n### Requirements\n\n- Rust\n- Terraform\n\nApply now")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_pages_from_mock() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 120,
+            ..mock_server::Config::default()
+        })
+        .await;
+        let client = Client::builder(reqwest::Client::new()).build();
+        let source = LeverSource {
+            companies: vec!["acme".to_owned()],
+            base_url: server.lever_url(),
+        };
+        let jobs = ats::scrape(source, client, HashSet::new())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(jobs.len(), 120);
+        assert!(matches!(&jobs[0], crate::Job::Lever { job } if job.company == "acme"));
+    }
+}
@@ -1,6 +1,25 @@
-pub(crate) mod cookies;
+pub mod arbeitsagentur;
+mod ats;
+pub mod checkpoint;
+pub mod client;
+pub mod cookies;
+pub mod fixtures;
+pub mod glassdoor;
+pub mod greenhouse;
+pub mod headers;
+pub mod indeed;
 pub mod instaffo;
+pub mod jsonld;
+pub mod lever;
 pub mod linkedin;
+pub mod markdown;
+pub mod personio;
+pub mod proxy;
+pub mod query;
+pub mod robots;
+pub mod source;
+pub mod stats;
+pub mod stepstone;
 pub mod xing;
 
 use serde::{Deserialize, Serialize};
@@ -12,6 +31,9 @@ pub enum Job {
     Xing {
         job: Box<xing::types::Job>,
         raw_data: Option<String>,
+        /// Markdown of the posting with its headings and lists, `raw_data` is the flattened text
+        #[serde(default)]
+        description: Option<String>,
     },
     Instaffo {
         job: Box<instaffo::Job>,
@@ -19,9 +41,54 @@ pub enum Job {
     Linkedin {
         job: Box<linkedin::Job>,
     },
-    Stepstone {},
-    Glassdoor {},
-    Indeed {},
+    Stepstone {
+        job: Box<stepstone::Job>,
+        raw_data: Option<String>,
+    },
+    Glassdoor {
+        job: Box<glassdoor::Job>,
+        raw_data: Option<String>,
+    },
+    Indeed {
+        job: Box<indeed::Job>,
+        raw_data: Option<String>,
+    },
+    /// A `JobPosting` found on a company's own career site
+    Generic {
+        job: Box<jsonld::Job>,
+    },
+    Greenhouse {
+        job: Box<greenhouse::Job>,
+    },
+    Lever {
+        job: Box<lever::Job>,
+    },
+    Personio {
+        job: Box<personio::Job>,
+    },
+    Arbeitsagentur {
+        job: Box<arbeitsagentur::Job>,
+        raw_data: Option<String>,
+    },
+}
+
+impl Job {
+    /// Id of the posting on its job board, e.g. the `linkedin_id` of a LinkedIn posting
+    pub fn site_id(&self) -> String {
+        match self {
+            Job::Xing { job, .. } => job.id.to_string(),
+            Job::Linkedin { job } => job.linkedin_id.clone(),
+            Job::Stepstone { job, .. } => job.stepstone_id.clone(),
+            Job::Glassdoor { job, .. } => job.glassdoor_id.clone(),
+            Job::Indeed { job, .. } => job.indeed_id.clone(),
+            Job::Instaffo { job: job_entry } => job_entry.job.uuid.clone(),
+            Job::Generic { job } => job.url.clone(),
+            Job::Greenhouse { job } => job.greenhouse_id.clone(),
+            Job::Lever { job } => job.lever_id.clone(),
+            Job::Personio { job } => job.personio_id.clone(),
+            Job::Arbeitsagentur { job, .. } => job.refnr.clone(),
+        }
+    }
 }
 
 impl Hash for Job {
@@ -34,13 +101,42 @@ impl Hash for Job {
                 job.linkedin_id.hash(state);
                 "linkedin".hash(state);
             }
-            Job::Stepstone {} => todo!(),
-            Job::Glassdoor {} => todo!(),
-            Job::Indeed {} => todo!(),
+            Job::Stepstone { job, .. } => {
+                job.stepstone_id.hash(state);
+                "stepstone".hash(state);
+            }
+            Job::Glassdoor { job, .. } => {
+                job.glassdoor_id.hash(state);
+                "glassdoor".hash(state);
+            }
+            Job::Indeed { job, .. } => {
+                job.indeed_id.hash(state);
+                "indeed".hash(state);
+            }
             Job::Instaffo { job: job_entry } => {
                 job_entry.job.uuid.hash(state);
                 "instaffo".hash(state);
             }
+            Job::Generic { job } => {
+                job.url.hash(state);
+                "generic".hash(state);
+            }
+            Job::Greenhouse { job } => {
+                job.greenhouse_id.hash(state);
+                "greenhouse".hash(state);
+            }
+            Job::Lever { job } => {
+                job.lever_id.hash(state);
+                "lever".hash(state);
+            }
+            Job::Personio { job } => {
+                job.personio_id.hash(state);
+                "personio".hash(state);
+            }
+            Job::Arbeitsagentur { job, .. } => {
+                job.refnr.hash(state);
+                "arbeitsagentur".hash(state);
+            }
         }
     }
 }
@@ -1,17 +1,21 @@
+use crate::checkpoint::Checkpoints;
+use crate::client::{Client, RateLimit};
+use crate::headers;
+use crate::markdown;
+use crate::query::{url_with_params, EmploymentType, Recency, SearchQuery};
+use crate::source::{base_url, JobSource, JobStream, SearchParams, SourceError};
+use async_trait::async_trait;
 use chrono::{Duration, Utc};
 use futures::{stream, Future, Stream, StreamExt};
 use lazy_static::lazy_static;
 use regex::Regex;
-use reqwest::header::{
-    HeaderValue, ACCEPT, ACCEPT_LANGUAGE, CACHE_CONTROL, CONNECTION, UPGRADE_INSECURE_REQUESTS,
-    USER_AGENT,
-};
-use reqwest::{header::HeaderMap, Client};
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, iter::Flatten};
 use tokio::sync::Mutex;
-use tokio::time::sleep;
+
+/// Live guest api, `LINKEDIN_BASE_URL` points the scraper somewhere else
+pub const BASE_URL: &str = "https://www.linkedin.com/jobs-guest";
 
 fn extract_job_id(job_url: &str) -> Option<String> {
     lazy_static! {
@@ -20,26 +24,156 @@ fn extract_job_id(job_url: &str) -> Option<String> {
     RE.captures(job_url)?.get(1).map(|m| m.as_str().to_owned())
 }
 
-fn job_search_url(query: &str, location: &str, offset: u32) -> String {
-    return format!(
-            "https://www.linkedin.com/jobs-guest/jobs/api/seeMoreJobPostings/search?location={}&keywords={}&start={}&f_TPR=r604800",
-            location, urlencoding::encode(query), offset
-        );
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExperienceLevel {
+    Internship,
+    EntryLevel,
+    Associate,
+    MidSenior,
+    Director,
+    Executive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workplace {
+    OnSite,
+    Remote,
+    Hybrid,
+}
+
+/// LinkedIn specific search filters, applied on top of a `SearchQuery`.
+/// Empty lists don't restrict the search
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    /// LinkedIn geo ids, e.g. `101282230` for Germany, every query is searched once per id
+    pub geo_ids: Vec<String>,
+    pub workplaces: Vec<Workplace>,
+    pub experience_levels: Vec<ExperienceLevel>,
+    pub job_types: Vec<EmploymentType>,
+}
+
+fn job_type_param(employment_type: EmploymentType) -> &'static str {
+    match employment_type {
+        EmploymentType::FullTime => "F",
+        EmploymentType::PartTime => "P",
+        EmploymentType::Freelance => "C",
+        EmploymentType::Internship => "I",
+        EmploymentType::Temporary => "T",
+    }
+}
+
+fn experience_level_param(experience_level: ExperienceLevel) -> &'static str {
+    match experience_level {
+        ExperienceLevel::Internship => "1",
+        ExperienceLevel::EntryLevel => "2",
+        ExperienceLevel::Associate => "3",
+        ExperienceLevel::MidSenior => "4",
+        ExperienceLevel::Director => "5",
+        ExperienceLevel::Executive => "6",
+    }
+}
+
+fn workplace_param(workplace: Workplace) -> &'static str {
+    match workplace {
+        Workplace::OnSite => "1",
+        Workplace::Remote => "2",
+        Workplace::Hybrid => "3",
+    }
+}
+
+fn join<T>(values: impl Iterator<Item = T>, param: fn(T) -> &'static str) -> Option<String> {
+    let mut joined: Vec<&'static str> = Vec::new();
+    for value in values.map(param) {
+        if !joined.contains(&value) {
+            joined.push(value);
+        }
+    }
+    Some(joined.join(",")).filter(|joined| !joined.is_empty())
+}
+
+fn job_search_url(
+    base_url: &str,
+    query: &SearchQuery,
+    filters: &Filters,
+    geo_id: Option<&str>,
+    offset: u32,
+) -> String {
+    let mut params = vec![
+        ("keywords", query.keywords.clone()),
+        ("start", offset.to_string()),
+    ];
+    if let Some(location) = &query.location {
+        params.push(("location", location.clone()));
+    }
+    if let Some(geo_id) = geo_id {
+        params.push(("geoId", geo_id.to_owned()));
+    }
+    if let Some(radius) = query.radius {
+        // linkedin measures the distance in miles
+        params.push(("distance", (radius * 5 / 8).to_string()));
+    }
+    // past week unless specified otherwise
+    let recency = query.recency.unwrap_or(Recency::Week);
+    if let Some(days) = recency.days() {
+        params.push(("f_TPR", format!("r{}", days * 24 * 60 * 60)));
+    }
+    let job_types = query
+        .employment_type
+        .into_iter()
+        .chain(filters.job_types.iter().copied());
+    if let Some(job_types) = join(job_types, job_type_param) {
+        params.push(("f_JT", job_types));
+    }
+    if let Some(levels) = join(
+        filters.experience_levels.iter().copied(),
+        experience_level_param,
+    ) {
+        params.push(("f_E", levels));
+    }
+    let workplaces = query
+        .remote
+        .then_some(Workplace::Remote)
+        .into_iter()
+        .chain(filters.workplaces.iter().copied());
+    if let Some(workplaces) = join(workplaces, workplace_param) {
+        params.push(("f_WT", workplaces));
+    }
+    url_with_params(
+        &format!("{}/jobs/api/seeMoreJobPostings/search", base_url),
+        params,
+    )
+}
+
+fn parse_job_ids(body: &str) -> Vec<String> {
+    let selector = Selector::parse(".base-card__full-link").unwrap();
+    let doc = Html::parse_document(body);
+    doc.select(&selector)
+        .map(|el| el.value().attr("href").and_then(extract_job_id))
+        .filter(Option::is_some)
+        .map(Option::unwrap)
+        .collect::<Vec<String>>()
 }
 
 lazy_static! {
     static ref SCRAPED_IDS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
 }
 
+/// Pages through the ids of a search, the offset of the next page is checkpointed
+/// once the ids of the previous one were consumed
 async fn scrape_job_ids(
     client: Client,
-    query: String,
-    location: String,
+    base_url: String,
+    query: SearchQuery,
+    filters: Filters,
+    geo_id: Option<String>,
+    checkpoints: Checkpoints,
 ) -> impl Stream<Item = Vec<String>> {
-    let mut offset = 0;
+    // the first page on the live site identifies the search, whichever host is scraped
+    let search = job_search_url(BASE_URL, &query, &filters, geo_id.as_deref(), 0);
     async_stream::stream! {
+        let mut offset: u32 = checkpoints.resume("linkedin", &search).await.unwrap_or(0);
         loop {
-            let url = job_search_url(&query, &location, offset);
+            let url = job_search_url(&base_url, &query, &filters, geo_id.as_deref(), offset);
             log::info!("GET {}", url);
             let resp = match client.get(&url).send().await {
                 Ok(resp) => resp,
@@ -57,27 +191,20 @@ async fn scrape_job_ids(
                 }
             };
 
-            if status == 429 {
-                log::info!("Rate limit exceeded (Status 429), sleeping...");
-                sleep(Duration::seconds(5).to_std().unwrap()).await;
-                continue;
-            }
-
             if status != 200 {
                 log::error!("Request not successful, status code: {}, body: {}", status, body);
                 break;
             }
 
-            let selector = Selector::parse(".base-card__full-link").unwrap();
-            let doc = Html::parse_document(&body);
-            let ids = doc.select(&selector).map(|el| {
-                el.value().attr("href").and_then(extract_job_id)
-            })
-                .filter(Option::is_some)
-                .map(Option::unwrap)
-                .collect::<Vec<String>>();
+            let ids = parse_job_ids(&body);
+            // past the last page the api answers with an empty list
+            if ids.is_empty() {
+                checkpoints.finish("linkedin", &search).await;
+                break;
+            }
             yield ids;
             offset += 25;
+            checkpoints.save("linkedin", &search, &offset).await;
         }
     }
 }
@@ -96,6 +223,9 @@ pub struct Job {
     pub company: Company,
     posting_date: Option<chrono::DateTime<Utc>>,
     raw_data: Option<String>,
+    /// `raw_data` as Markdown
+    #[serde(default)]
+    pub description: Option<String>,
     criteria: JobCriteria,
 }
 
@@ -121,11 +251,8 @@ fn try_convert_age(age: &str) -> Option<chrono::DateTime<Utc>> {
     Some(today)
 }
 
-pub async fn scrape_job(client: Client, id: String) -> Option<crate::Job> {
-    let job_link = format!(
-        "https://www.linkedin.com/jobs-guest/jobs/api/jobPosting/{}",
-        id
-    );
+pub async fn scrape_job(client: Client, base_url: &str, id: String) -> Option<crate::Job> {
+    let job_link = format!("{}/jobs/api/jobPosting/{}", base_url, id);
     log::info!("Sending GET to: {}", job_link);
     let response = match client.get(job_link).send().await {
         Ok(resp) => resp,
@@ -143,12 +270,6 @@ pub async fn scrape_job(client: Client, id: String) -> Option<crate::Job> {
         }
     };
 
-    if status == 429 {
-        log::info!("Rate limit exceeded (Status 429), sleeping...");
-        sleep(Duration::seconds(5).to_std().unwrap()).await;
-        return None;
-    }
-
     if status != 200 {
         log::error!(
             "Request not successful, status code: {}, body: {}",
@@ -195,10 +316,13 @@ pub async fn scrape_job(client: Client, id: String) -> Option<crate::Job> {
         .map(|s| try_convert_age(&s))
         .flatten();
     let raw_data_selector = Selector::parse("div.description__text").unwrap();
-    let raw_data: Option<String> = doc
-        .select(&raw_data_selector)
-        .next()
-        .map(|el| el.text().filter(|c| !c.is_empty()).map(str::trim).collect());
+    let raw_data_element = doc.select(&raw_data_selector).next();
+    let raw_data: Option<String> =
+        raw_data_element.map(|el| el.text().filter(|c| !c.is_empty()).map(str::trim).collect());
+    let description = raw_data_element.map(markdown::from_element);
+    if raw_data.is_none() {
+        client.extraction_failed();
+    }
     let criteria_selector = Selector::parse(".description__job-criteria-text").unwrap();
     let criteria: Vec<String> = doc
         .select(&criteria_selector)
@@ -218,6 +342,7 @@ pub async fn scrape_job(client: Client, id: String) -> Option<crate::Job> {
         },
         posting_date: age,
         raw_data,
+        description,
         criteria: JobCriteria {
             seniority,
             employment_type,
@@ -228,35 +353,46 @@ pub async fn scrape_job(client: Client, id: String) -> Option<crate::Job> {
     Some(crate::Job::Linkedin { job })
 }
 
+/// Scrape all jobs for the given queries, once per geo id if the filters contain any.
+/// Queries without a recency only search the postings of the past week,
+/// ids in `known_ids` are left out without requesting their posting
 pub async fn scrape(
-    queries: Vec<String>,
-    locations: Vec<String>,
-) -> impl Stream<Item = Option<crate::Job>> {
-    log::info!("creating client and producing query products");
-    let mut headers = HeaderMap::new();
-    headers.insert(ACCEPT, HeaderValue::from_static("text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.9"));
-    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.9"));
-    headers.insert(CACHE_CONTROL, HeaderValue::from_static("max-age=0"));
-    headers.insert(CONNECTION, HeaderValue::from_static("keep-alive"));
-    headers.insert("sec-fetch-dest", HeaderValue::from_static("document"));
-    headers.insert("sec-fetch-mode", HeaderValue::from_static("navigate"));
-    headers.insert("sec-fetch-site", HeaderValue::from_static("none"));
-    headers.insert("sec-fetch-user", HeaderValue::from_static("?1"));
-    headers.insert(UPGRADE_INSECURE_REQUESTS, HeaderValue::from_static("1"));
-    headers.insert(USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/112.0.0.0 Safari/537.36"));
-    let client = Client::builder().default_headers(headers).build().unwrap();
-    let product = queries
+    base_url: String,
+    queries: Vec<SearchQuery>,
+    filters: Filters,
+    known_ids: HashSet<String>,
+    checkpoints: Checkpoints,
+) -> impl Stream<Item = Result<crate::Job, SourceError>> {
+    log::info!("creating client");
+    // the guest api answers with 429 quickly, the client waits and retries those requests
+    let client = Client::builder(reqwest::Client::new())
+        .profile(headers::random())
+        .rate_limit("www.linkedin.com", RateLimit::per_second(1.0))
+        .build();
+    let geo_ids = if filters.geo_ids.is_empty() {
+        vec![None]
+    } else {
+        filters.geo_ids.iter().cloned().map(Some).collect()
+    };
+    let searches = queries
         .iter()
         .flat_map(|query| {
-            locations
+            geo_ids
                 .iter()
-                .map(move |location| (query.to_owned(), location.to_owned()))
+                .map(move |geo_id| (query.clone(), geo_id.clone()))
         })
-        .collect::<Vec<(String, String)>>();
-    let mut scraped_ids: HashSet<String> = HashSet::new();
+        .collect::<Vec<(SearchQuery, Option<String>)>>();
+    let mut scraped_ids: HashSet<String> = known_ids;
     async_stream::stream! {
-        for (query, location) in product {
-            let id_chunks = scrape_job_ids(client.clone(), query, location).await;
+        for (query, geo_id) in searches {
+            let id_chunks = scrape_job_ids(
+                client.clone(),
+                base_url.clone(),
+                query,
+                filters.clone(),
+                geo_id,
+                checkpoints.clone(),
+            ).await;
             tokio::pin!(id_chunks);
             while let Some(ids) = id_chunks.next().await {
                 for id in ids {
@@ -264,17 +400,60 @@ pub async fn scrape(
                         continue;
                     }
                     scraped_ids.insert(id.clone());
-                    let job = scrape_job(client.clone(), id).await;
-                    yield job;
+                    let job = scrape_job(client.clone(), &base_url, id.clone()).await;
+                    yield job.ok_or(SourceError::JobNotScraped(id));
                 }
             }
         }
     }
 }
 
+pub struct LinkedinSource {
+    pub filters: Filters,
+    pub base_url: String,
+}
+
+impl Default for LinkedinSource {
+    fn default() -> Self {
+        Self {
+            filters: Filters::default(),
+            base_url: base_url("LINKEDIN_BASE_URL", BASE_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl JobSource for LinkedinSource {
+    fn name(&self) -> &'static str {
+        "linkedin"
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        scrape(
+            self.base_url.clone(),
+            params.queries,
+            self.filters.clone(),
+            params.known_ids,
+            params.checkpoints,
+        )
+        .await
+        .boxed()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::checkpoint::MemoryCheckpoints;
+    use crate::fixtures::{self, Fixtures};
+    use std::sync::Arc;
+
+    fn fixture_client(name: &str) -> Client {
+        Client::builder(reqwest::Client::new())
+            .profile(headers::random())
+            .fixtures(Fixtures::from_env(fixtures::path(name)))
+            .build()
+    }
 
     #[test]
     fn test_try_convert_age() {
@@ -284,4 +463,117 @@ mod test {
         let res = res.unwrap();
         assert!(res < chrono::Utc::now());
     }
+
+    #[test]
+    fn test_job_search_url() {
+        let mut query = SearchQuery::new("C++");
+        query.location = Some("Germany".to_owned());
+        let filters = Filters::default();
+        let url = job_search_url(BASE_URL, &query, &filters, None, 25);
+        assert_eq!(
+            url,
+            "https://www.linkedin.com/jobs-guest/jobs/api/seeMoreJobPostings/search?keywords=C%2B%2B&start=25&location=Germany&f_TPR=r604800"
+        );
+        query.recency = Some(Recency::Any);
+        assert!(!job_search_url(BASE_URL, &query, &filters, None, 0).contains("f_TPR"));
+    }
+
+    #[test]
+    fn test_job_search_url_filters() {
+        let query = SearchQuery {
+            recency: Some(Recency::Day),
+            remote: true,
+            ..SearchQuery::new("Rust")
+        };
+        let filters = Filters {
+            geo_ids: vec!["101282230".to_owned()],
+            workplaces: vec![Workplace::Remote, Workplace::Hybrid],
+            experience_levels: vec![ExperienceLevel::EntryLevel, ExperienceLevel::MidSenior],
+            job_types: vec![EmploymentType::FullTime],
+        };
+        let url = job_search_url(BASE_URL, &query, &filters, Some("101282230"), 0);
+        assert_eq!(
+            url,
+            "https://www.linkedin.com/jobs-guest/jobs/api/seeMoreJobPostings/search?keywords=Rust&start=0&geoId=101282230&f_TPR=r86400&f_JT=F&f_E=2%2C4&f_WT=2%2C3"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_job_ids() {
+        let client = fixture_client("linkedin_job_ids");
+        let query = SearchQuery::new("Rust");
+        let ids = scrape_job_ids(
+            client,
+            BASE_URL.to_owned(),
+            query,
+            Filters::default(),
+            None,
+            Checkpoints::default(),
+        )
+        .await
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(ids, vec![vec!["3581234567", "3579876543"]]);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_job() {
+        let client = fixture_client("linkedin_job");
+        let job = scrape_job(client, BASE_URL, "3581234567".to_owned()).await;
+        let Some(crate::Job::Linkedin { job }) = job else {
+            panic!("Failed to scrape job");
+        };
+        assert_eq!(job.title.as_deref(), Some("Rust Developer"));
+        assert_eq!(job.company.name.as_deref(), Some("Helsing"));
+        assert_eq!(job.location.as_deref(), Some("Munich, Bavaria, Germany"));
+        assert!(job.posting_date.is_some());
+        assert!(job
+            .raw_data
+            .unwrap()
+            .contains("3+ years of experience with Rust"));
+        assert!(job.description.unwrap().ends_with(
+            "platform.\n\n- 3+ years of experience with Rust\n- Experience with async Rust and tokio"
+        ));
+        assert_eq!(job.criteria.seniority.as_deref(), Some("Mid-Senior level"));
+        assert_eq!(
+            job.criteria.industries.as_deref(),
+            Some("Defense and Space Manufacturing")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumes_search_from_checkpoint() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 60,
+            ..mock_server::Config::default()
+        })
+        .await;
+        let client = Client::builder(reqwest::Client::new())
+            .default_rate_limit(RateLimit {
+                requests_per_second: 100.0,
+                burst: 10,
+            })
+            .build();
+        let store = Arc::new(MemoryCheckpoints::default());
+        let job_ids = |resume: bool| {
+            scrape_job_ids(
+                client.clone(),
+                server.linkedin_url(),
+                SearchQuery::new("Rust"),
+                Filters::default(),
+                None,
+                Checkpoints::new(store.clone(), resume),
+            )
+        };
+        // interrupted while the postings of the second page are scraped
+        let interrupted = job_ids(false).await.take(2).collect::<Vec<_>>().await;
+        assert_eq!(interrupted.len(), 2);
+        assert_eq!(store.len(), 1);
+
+        let resumed = job_ids(true).await.collect::<Vec<_>>().await;
+        assert_eq!(resumed.len(), 2);
+        assert_eq!(resumed[0][0], "3500000025");
+        assert_eq!(resumed[1].len(), 10);
+        assert!(store.is_empty());
+    }
 }
@@ -0,0 +1,219 @@
+use ego_tree::NodeRef;
+use scraper::{ElementRef, Html, Node};
+
+/// Elements that never contain posting text
+const SKIPPED: [&str; 7] = [
+    "script", "style", "noscript", "template", "button", "svg", "icon",
+];
+const BLOCKS: [&str; 11] = [
+    "p",
+    "div",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "main",
+    "blockquote",
+    "table",
+    "tr",
+    "hr",
+];
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    /// Open lists from the outside in, ordered lists carry the number of their last item
+    lists: Vec<Option<usize>>,
+    /// End of the last list marker, content right after it stays on the marker's line
+    marker_end: usize,
+}
+
+impl Writer {
+    fn trim_end(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+    }
+
+    fn line(&mut self) {
+        if self.out.len() == self.marker_end {
+            return;
+        }
+        self.trim_end();
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    /// Starts a new paragraph, inside of lists a new line is enough
+    fn block(&mut self) {
+        self.line();
+        if self.lists.is_empty() && !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let mut collapsed = String::with_capacity(text.len());
+        for (i, word) in text.split_whitespace().enumerate() {
+            if i > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        let at_line_start = self.out.is_empty() || self.out.ends_with(['\n', ' ']);
+        if text.starts_with(char::is_whitespace) && !at_line_start {
+            self.out.push(' ');
+        }
+        self.out.push_str(&collapsed);
+        if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+            self.out.push(' ');
+        }
+    }
+
+    fn children(&mut self, node: NodeRef<Node>) {
+        for child in node.children() {
+            self.node(child);
+        }
+    }
+
+    /// Wraps the children in `open` and `close`, e.g. `**` for bold text. Whitespace at the
+    /// edges of the children is kept outside the markers, none is added
+    fn inline(&mut self, node: NodeRef<Node>, open: &str, close: &str) {
+        let start = self.out.len();
+        self.children(node);
+        let content = self.out.split_off(start);
+        let trimmed = content.trim();
+        if trimmed.is_empty() {
+            self.out.push_str(&content);
+            return;
+        }
+        if content.starts_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+        self.out.push_str(open);
+        self.out.push_str(trimmed);
+        self.out.push_str(close);
+        if content.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    fn node(&mut self, node: NodeRef<Node>) {
+        match node.value() {
+            Node::Text(text) => self.text(text),
+            Node::Element(_) => self.element(ElementRef::wrap(node).unwrap()),
+            _ => self.children(node),
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        match name {
+            name if SKIPPED.contains(&name) => {}
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap();
+                self.block();
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+                self.children(*element);
+                self.block();
+            }
+            "br" => self.line(),
+            "ul" | "ol" => {
+                self.line();
+                self.lists.push((name == "ol").then_some(0));
+                self.children(*element);
+                self.lists.pop();
+                self.block();
+            }
+            "li" => {
+                self.line();
+                let depth = self.lists.len().saturating_sub(1);
+                self.out.push_str(&"  ".repeat(depth));
+                match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        let number = *number;
+                        self.out.push_str(&format!("{}. ", number));
+                    }
+                    _ => self.out.push_str("- "),
+                }
+                self.marker_end = self.out.len();
+                self.children(*element);
+                self.line();
+            }
+            "strong" | "b" => self.inline(*element, "**", "**"),
+            "em" | "i" => self.inline(*element, "_", "_"),
+            "a" => match element.value().attr("href") {
+                Some(href) if href.starts_with("http") => {
+                    self.inline(*element, "[", &format!("]({})", href))
+                }
+                _ => self.children(*element),
+            },
+            name if BLOCKS.contains(&name) => {
+                self.block();
+                self.children(*element);
+                self.block();
+            }
+            _ => self.children(*element),
+        }
+    }
+
+    fn finish(self) -> String {
+        self.out.trim().to_owned()
+    }
+}
+
+/// Markdown of the element's content, headings, lists and paragraphs are kept
+pub fn from_element(element: ElementRef) -> String {
+    let mut writer = Writer::default();
+    writer.children(*element);
+    writer.finish()
+}
+
+/// Markdown of a html fragment, e.g. the description of a schema.org `JobPosting`
+pub fn from_html(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    from_element(fragment.root_element())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sections_are_kept() {
+        let html = r#"<div><h3>Gehaltsspanne</h3><p>Angabe des Arbeitgebers</p><p>60.000 € – 90.000 €</p>
+            <h3>Deine Aufgaben</h3>
+            <ul>
+                <li>Du entwickelst <strong>Backend Services</strong> in Rust</li>
+                <li>Du betreust <a href="https://example.com/team">unser Team</a></li>
+            </ul>
+            <p>Wir freuen uns<br>auf dich!</p>
+            <script>tracking()</script><button>Alle Ergebnisse</button></div>"#;
+        assert_eq!(
+            from_html(html),
+            "### Gehaltsspanne\n\nAngabe des Arbeitgebers\n\n60.000 € – 90.000 €\n\n\
+             ### Deine Aufgaben\n\n\
+             - Du entwickelst **Backend Services** in Rust\n\
+             - Du betreust [unser Team](https://example.com/team)\n\n\
+             Wir freuen uns\nauf dich!"
+        );
+    }
+
+    #[test]
+    fn test_nested_lists() {
+        let html = "<ol><li>Rust<ul><li>tokio</li><li><p>serde</p></li></ul></li><li>Go</li></ol>";
+        assert_eq!(from_html(html), "1. Rust\n  - tokio\n  - serde\n2. Go");
+    }
+
+    #[test]
+    fn test_inline_markup_adds_no_whitespace() {
+        let html = r#"<p>Du entwickelst <b>Java</b>-Anwendungen mit <i>Spring</i>, <strong>Rust </strong>und
+            <a href="https://example.com/stack">Go</a>.</p>"#;
+        assert_eq!(
+            from_html(html),
+            "Du entwickelst **Java**-Anwendungen mit _Spring_, **Rust** und [Go](https://example.com/stack)."
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ats::{self, Ats};
+use crate::client::Client;
+use crate::markdown;
+use crate::source::{base_url, env_list, JobSource, JobStream, SearchParams};
+
+type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Request error: '{0}'")]
+    Request(#[from] reqwest::Error),
+    #[error("Failed to scrape data from: '{0}'")]
+    RequestNotOk(String),
+    #[error("Invalid xml feed: '{0}'")]
+    Xml(#[from] quick_xml::DeError),
+}
+
+/// Career site of a company, `{company}` is replaced with its name.
+/// `PERSONIO_BASE_URL` points the scraper somewhere else
+pub const BASE_URL: &str = "https://{company}.jobs.personio.de";
+/// Names of the companies to scrape as in <company>.jobs.personio.de, separated by `,`
+pub const COMPANIES_ENV: &str = "PERSONIO_COMPANIES";
+
+#[derive(Deserialize)]
+struct Feed {
+    #[serde(default, rename = "position")]
+    positions: Vec<Position>,
+}
+
+#[derive(Deserialize, Default)]
+struct Offices {
+    #[serde(default)]
+    office: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Section {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize, Default)]
+struct Descriptions {
+    #[serde(default, rename = "jobDescription")]
+    sections: Vec<Section>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Position {
+    id: u64,
+    name: String,
+    office: Option<String>,
+    #[serde(default)]
+    additional_offices: Offices,
+    department: Option<String>,
+    employment_type: Option<String>,
+    seniority: Option<String>,
+    schedule: Option<String>,
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    job_descriptions: Descriptions,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Job {
+    /// Position ids are only unique per company, this is `<company>-<id>`
+    pub personio_id: String,
+    pub company: String,
+    pub title: String,
+    /// Main office first
+    pub offices: Vec<String>,
+    pub department: Option<String>,
+    /// e.g. "permanent" or "intern"
+    pub employment_type: Option<String>,
+    pub seniority: Option<String>,
+    /// "full-time" or "part-time"
+    pub schedule: Option<String>,
+    pub link: String,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Sections of the description as Markdown, each under its heading
+    pub description: Option<String>,
+}
+
+fn company_url(base_url: &str, company: &str) -> String {
+    base_url.replace("{company}", company)
+}
+
+/// Empty elements of the feed are read as empty strings
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+fn convert(base_url: &str, company: &str, position: Position) -> Job {
+    let html = position
+        .job_descriptions
+        .sections
+        .iter()
+        .map(|section| format!("<h3>{}</h3>{}", section.name, section.value))
+        .collect::<String>();
+    let offices = non_empty(position.office)
+        .into_iter()
+        .chain(position.additional_offices.office)
+        .filter(|office| !office.trim().is_empty())
+        .collect();
+    Job {
+        personio_id: format!("{}-{}", company, position.id),
+        company: company.to_owned(),
+        title: position.name,
+        offices,
+        department: non_empty(position.department),
+        employment_type: non_empty(position.employment_type),
+        seniority: non_empty(position.seniority),
+        schedule: non_empty(position.schedule),
+        link: format!("{}/job/{}", company_url(base_url, company), position.id),
+        created_at: position.created_at,
+        description: (!html.is_empty()).then(|| markdown::from_html(&html)),
+    }
+}
+
+fn parse_feed(base_url: &str, company: &str, body: &str) -> Result<Vec<Job>> {
+    let feed = quick_xml::de::from_str::<Feed>(body)?;
+    Ok(feed
+        .positions
+        .into_iter()
+        .map(|position| convert(base_url, company, position))
+        .collect())
+}
+
+async fn scrape_feed(client: &Client, base_url: &str, company: &str) -> Result<Vec<Job>> {
+    let url = format!("{}/xml", company_url(base_url, company));
+    log::debug!("requesting personio feed of: {}", company);
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Err(Error::RequestNotOk(url));
+    }
+    let body = resp.text().await?;
+    parse_feed(base_url, company, &body)
+}
+
+#[derive(Clone)]
+pub struct PersonioSource {
+    pub companies: Vec<String>,
+    pub base_url: String,
+}
+
+impl Default for PersonioSource {
+    fn default() -> Self {
+        Self {
+            companies: env_list(COMPANIES_ENV),
+            base_url: base_url("PERSONIO_BASE_URL", BASE_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl Ats for PersonioSource {
+    const NAME: &'static str = "personio";
+    const CONFIG: &'static str = "PERSONIO_COMPANIES, companies on jobs.personio.de";
+    type Error = Error;
+
+    fn companies(&self) -> &[String] {
+        &self.companies
+    }
+
+    async fn company_jobs(&self, client: &Client, company: &str) -> Result<Vec<crate::Job>> {
+        let jobs = scrape_feed(client, &self.base_url, company).await?;
+        Ok(jobs
+            .into_iter()
+            .map(|job| crate::Job::Personio { job: Box::new(job) })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl JobSource for PersonioSource {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        ats::jobs(self, params.known_ids)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::StreamExt;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_parse_feed() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <workzag-jobs>
+            <position>
+                <id>1034567</id>
+                <subcompany>Acme GmbH</subcompany>
+                <office>Munich</office>
+                <additionalOffices><office>Berlin</office></additionalOffices>
+                <department></department>
+                <recruitingCategory/>
+                <name>Senior Rust Developer (m/w/d)</name>
+                <jobDescriptions>
+                    <jobDescription>
+                        <name>Deine Aufgaben</name>
+                        <value><![CDATA[<ul><li>Services in <strong>Rust</strong></li></ul>]]></value>
+                    </jobDescription>
+                    <jobDescription>
+                        <name>Dein Profil</name>
+                        <value><![CDATA[<p>Erfahrung mit Tokio</p>]]></value>
+                    </jobDescription>
+                </jobDescriptions>
+                <employmentType>permanent</employmentType>
+                <seniority>experienced</seniority>
+                <schedule>full-time</schedule>
+                <createdAt>2023-05-02T08:00:00+00:00</createdAt>
+            </position>
+            <position>
+                <id>1034568</id>
+                <office>Munich</office>
+                <name>Werkstudent Data (m/w/d)</name>
+                <employmentType>intern</employmentType>
+            </position>
+        </workzag-jobs>"#;
+        let jobs = parse_feed(BASE_URL, "acme", body).expect("Failed to parse feed");
+        assert_eq!(jobs.len(), 2);
+        let job = &jobs[0];
+        assert_eq!(job.personio_id, "acme-1034567");
+        assert_eq!(job.link, "https://acme.jobs.personio.de/job/1034567");
+        assert_eq!(job.offices, vec!["Munich", "Berlin"]);
+        assert_eq!(job.department, None);
+        assert_eq!(job.schedule.as_deref(), Some("full-time"));
+        assert_eq!(
+            job.description.as_deref(),
+            Some("### Deine Aufgaben\n\n- Services in **Rust**\n\n### Dein Profil\n\nErfahrung mit Tokio")
+        );
+        assert_eq!(jobs[1].description, None);
+        assert_eq!(jobs[1].created_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_feeds_from_mock() {
+        let server = mock_server::MockServer::start(mock_server::Config {
+            jobs: 4,
+            ..mock_server::Config::default()
+        })
+        .await;
+        let client = Client::builder(reqwest::Client::new()).build();
+        let source = PersonioSource {
+            companies: vec!["acme".to_owned(), "missing".to_owned()],
+            base_url: server.personio_url(),
+        };
+        let jobs = ats::scrape(source, client, HashSet::new())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(jobs.len(), 4);
+        assert!(jobs.iter().all(|job| job.site_id().starts_with("acme-")));
+    }
+}
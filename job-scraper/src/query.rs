@@ -0,0 +1,109 @@
+use std::str::FromStr;
+
+use reqwest::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmploymentType {
+    FullTime,
+    PartTime,
+    Freelance,
+    Internship,
+    Temporary,
+}
+
+/// Maximum age of the postings returned by a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recency {
+    Day,
+    Week,
+    Month,
+    Any,
+}
+
+impl Recency {
+    pub fn days(&self) -> Option<u32> {
+        match self {
+            Recency::Day => Some(1),
+            Recency::Week => Some(7),
+            Recency::Month => Some(30),
+            Recency::Any => None,
+        }
+    }
+}
+
+impl FromStr for Recency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" | "24h" => Ok(Recency::Day),
+            "week" => Ok(Recency::Week),
+            "month" => Ok(Recency::Month),
+            "any" => Ok(Recency::Any),
+            _ => Err(format!(
+                "Unknown recency: {}, expected day, week, month or any",
+                s
+            )),
+        }
+    }
+}
+
+/// A job search, every source translates it into its own url parameters.
+/// Values are plain text, encoding is done by the source building the url
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub keywords: String,
+    pub location: Option<String>,
+    /// Search radius around `location` in kilometers
+    pub radius: Option<u32>,
+    pub employment_type: Option<EmploymentType>,
+    /// `None` leaves the window up to the source's default
+    pub recency: Option<Recency>,
+    /// Only return remote postings
+    pub remote: bool,
+}
+
+impl SearchQuery {
+    pub fn new(keywords: impl Into<String>) -> Self {
+        Self {
+            keywords: keywords.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&str> for SearchQuery {
+    fn from(keywords: &str) -> Self {
+        Self::new(keywords)
+    }
+}
+
+/// Appends form-encoded query parameters to `base`
+pub(crate) fn url_with_params<'a>(
+    base: &str,
+    params: impl IntoIterator<Item = (&'a str, String)>,
+) -> String {
+    Url::parse_with_params(base, params)
+        .expect("Base url of a source must be valid")
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_url_with_params_encodes_special_characters() {
+        let url = url_with_params(
+            "https://example.com/search",
+            vec![
+                ("keywords", "C++".to_owned()),
+                ("other", "C# .NET".to_owned()),
+            ],
+        );
+        assert_eq!(
+            url,
+            "https://example.com/search?keywords=C%2B%2B&other=C%23+.NET"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::{Response, Url};
+
+/// Set to `true` or `1` to make every scraper follow the robots.txt of the hosts it visits
+pub const ROBOTS_ENV: &str = "SCRAPER_RESPECT_ROBOTS";
+
+#[derive(Debug)]
+struct Rule {
+    allow: bool,
+    /// Length of the pattern in the file, the longest matching rule wins
+    length: usize,
+    pattern: Regex,
+}
+
+impl Rule {
+    fn new(allow: bool, pattern: &str) -> Self {
+        let (pattern, anchored) = match pattern.strip_suffix('$') {
+            Some(pattern) => (pattern, true),
+            None => (pattern, false),
+        };
+        let mut regex = String::from("^");
+        regex.push_str(
+            &pattern
+                .split('*')
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(".*"),
+        );
+        if anchored {
+            regex.push('$');
+        }
+        Self {
+            allow,
+            length: pattern.len(),
+            pattern: Regex::new(&regex).expect("Escaped robots.txt pattern must be valid"),
+        }
+    }
+}
+
+/// Rules of a robots.txt for the `*` user agent, the scrapers identify as
+/// regular browsers so groups of named crawlers never apply to them
+#[derive(Debug, Default)]
+pub struct Robots {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    pub fn parse(content: &str) -> Self {
+        let mut robots = Robots::default();
+        // user agents of the current group, a rule line ends the list
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        agents.clear();
+                        in_rules = false;
+                    }
+                    agents.push(value.to_owned());
+                }
+                key => {
+                    in_rules = true;
+                    if !agents.iter().any(|agent| agent == "*") {
+                        continue;
+                    }
+                    match key {
+                        // an empty disallow allows everything
+                        "disallow" if !value.is_empty() => {
+                            robots.rules.push(Rule::new(false, value))
+                        }
+                        "allow" if !value.is_empty() => robots.rules.push(Rule::new(true, value)),
+                        "crawl-delay" => {
+                            robots.crawl_delay = value
+                                .parse::<f64>()
+                                .ok()
+                                .filter(|delay| delay.is_finite() && *delay > 0.0)
+                                .map(Duration::from_secs_f64)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        robots
+    }
+
+    /// Used when the robots.txt can't be fetched
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![Rule::new(false, "/")],
+            crawl_delay: None,
+        }
+    }
+
+    /// Checks path and query of `url`, on a tie between allow and disallow the allow wins
+    pub fn is_allowed(&self, url: &Url) -> bool {
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_owned(),
+        };
+        self.rules
+            .iter()
+            .filter(|rule| rule.pattern.is_match(&path))
+            .max_by_key(|rule| (rule.length, rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+/// robots.txt of every host visited during the run, fetched once per host
+#[derive(Default)]
+pub struct RobotsCache {
+    robots: tokio::sync::Mutex<HashMap<String, Arc<Robots>>>,
+    skipped: Mutex<HashMap<String, u32>>,
+}
+
+impl RobotsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache for the runs that opted in through `SCRAPER_RESPECT_ROBOTS`
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var(ROBOTS_ENV).ok()?;
+        matches!(enabled.to_lowercase().as_str(), "1" | "true").then(Self::new)
+    }
+
+    /// robots.txt of the host of `url`, hosts that can't be reached are not crawled at all.
+    /// `fetch` is only called on a cache miss, with the url of the file
+    pub(crate) async fn get<F, Fut>(&self, url: &Url, fetch: F) -> Arc<Robots>
+    where
+        F: FnOnce(Url) -> Fut,
+        Fut: Future<Output = reqwest::Result<Response>>,
+    {
+        let origin = url.origin().ascii_serialization();
+        // held while fetching so concurrent requests don't fetch the same file
+        let mut robots = self.robots.lock().await;
+        if let Some(robots) = robots.get(&origin) {
+            return robots.clone();
+        }
+        let robots_url = url
+            .join("/robots.txt")
+            .expect("Origin must be a valid base");
+        let parsed = match fetch(robots_url.clone()).await {
+            Ok(resp) if resp.status().is_success() => match resp.text().await {
+                Ok(content) => Robots::parse(&content),
+                Err(e) => {
+                    log::warn!(
+                        "Failed to read {}: {}, not crawling the host",
+                        robots_url,
+                        e
+                    );
+                    Robots::disallow_all()
+                }
+            },
+            // RFC 9309: a missing robots.txt puts no restrictions on crawling
+            Ok(resp) if resp.status().is_client_error() => Robots::default(),
+            Ok(resp) => {
+                log::warn!(
+                    "{} is unavailable (Status {}), not crawling the host",
+                    robots_url,
+                    resp.status()
+                );
+                Robots::disallow_all()
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to fetch {}: {}, not crawling the host",
+                    robots_url,
+                    e
+                );
+                Robots::disallow_all()
+            }
+        };
+        let parsed = Arc::new(parsed);
+        robots.insert(origin, parsed.clone());
+        parsed
+    }
+
+    pub(crate) fn skip(&self, url: &Url) {
+        log::info!("Skipping {}, disallowed by robots.txt", url);
+        let host = url.host_str().unwrap_or_default().to_owned();
+        *self.skipped.lock().unwrap().entry(host).or_default() += 1;
+    }
+
+    /// Number of skipped requests per host
+    pub fn skipped(&self) -> HashMap<String, u32> {
+        self.skipped.lock().unwrap().clone()
+    }
+}
+
+lazy_static! {
+    static ref SHARED: Option<Arc<RobotsCache>> = RobotsCache::from_env().map(Arc::new);
+}
+
+/// Cache shared by every scraper, `None` unless `SCRAPER_RESPECT_ROBOTS` is set
+pub fn shared() -> Option<Arc<RobotsCache>> {
+    SHARED.clone()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ROBOTS: &str = "
+# comments are ignored
+User-agent: Googlebot
+Disallow: /
+
+User-agent: Bingbot
+User-agent: *
+Disallow: /jobs/search   # trailing comment
+Allow: /jobs/search/public
+Disallow: /*.pdf$
+Disallow: /private*/
+Allow: /private-ok/
+Crawl-delay: 2.5
+";
+
+    fn allowed(robots: &Robots, path: &str) -> bool {
+        let url = Url::parse("https://www.example.com")
+            .unwrap()
+            .join(path)
+            .unwrap();
+        robots.is_allowed(&url)
+    }
+
+    #[test]
+    fn test_rules() {
+        let robots = Robots::parse(ROBOTS);
+        assert!(allowed(&robots, "/"));
+        assert!(allowed(&robots, "/jobs/view/1"));
+        assert!(!allowed(&robots, "/jobs/search?keywords=rust"));
+        assert!(allowed(&robots, "/jobs/search/public?keywords=rust"));
+        assert!(!allowed(&robots, "/files/cv.pdf"));
+        assert!(allowed(&robots, "/files/cv.pdf?download=1"));
+        assert!(!allowed(&robots, "/private-area/1"));
+        assert!(allowed(&robots, "/private-ok/1"));
+        assert_eq!(robots.crawl_delay(), Some(Duration::from_millis(2500)));
+    }
+
+    #[test]
+    fn test_allow_wins_ties() {
+        let robots = Robots::parse("User-agent: *\nDisallow: /jobs\nAllow: /jobs\n");
+        assert!(allowed(&robots, "/jobs"));
+    }
+
+    #[test]
+    fn test_other_agents_are_ignored() {
+        let robots = Robots::parse("User-agent: Googlebot\nDisallow: /\nCrawl-delay: 10\n");
+        assert!(allowed(&robots, "/jobs"));
+        assert_eq!(robots.crawl_delay(), None);
+        assert!(!allowed(&Robots::disallow_all(), "/jobs"));
+        assert!(allowed(&Robots::parse(""), "/jobs"));
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use thiserror::Error;
+
+use crate::checkpoint::Checkpoints;
+use crate::query::SearchQuery;
+use crate::{
+    arbeitsagentur, glassdoor, greenhouse, indeed, instaffo, jsonld, lever, linkedin, personio,
+    stepstone, xing, Job,
+};
+
+#[derive(Debug, Error)]
+pub enum SourceError {
+    #[error("Request error: '{0}'")]
+    Request(#[from] reqwest::Error),
+    #[error("Failed to scrape job: '{0}'")]
+    JobNotScraped(String),
+    #[error("Missing configuration: '{0}'")]
+    MissingConfig(&'static str),
+    #[error("Session expired for {0}, log in again and update the stored cookies")]
+    SessionExpired(&'static str),
+    #[error("Login to {0} failed, check the credentials")]
+    LoginFailed(&'static str),
+    #[error("Cookie jar error: '{0}'")]
+    Cookies(#[from] crate::cookies::Error),
+}
+
+pub type JobStream = BoxStream<'static, Result<Job, SourceError>>;
+
+/// Search input shared by every job source
+#[derive(Debug, Clone, Default)]
+pub struct SearchParams {
+    pub queries: Vec<SearchQuery>,
+    /// Site ids of postings stored by earlier runs, see `Job::site_id`.
+    /// Sources skip these before requesting their detail pages
+    pub known_ids: HashSet<String>,
+    /// Where LinkedIn and Instaffo keep their position, so an interrupted run can be resumed
+    pub checkpoints: Checkpoints,
+}
+
+/// A job board that can be searched for postings
+#[async_trait]
+pub trait JobSource: Send + Sync {
+    /// Unique lowercase name of the source, used to select it from the CLI
+    fn name(&self) -> &'static str;
+
+    async fn jobs(&self, params: SearchParams) -> JobStream;
+}
+
+/// Base url of a source, the live site unless `env` is set,
+/// e.g. to scrape a local mock server
+pub(crate) fn base_url(env: &str, default: &str) -> String {
+    std::env::var(env)
+        .map(|url| url.trim_end_matches('/').to_owned())
+        .unwrap_or_else(|_| default.to_owned())
+}
+
+/// Values of a comma separated list in `env`, e.g. the companies of an applicant tracking system
+pub(crate) fn env_list(env: &str) -> Vec<String> {
+    std::env::var(env)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// All available job sources
+pub fn registry() -> Vec<Box<dyn JobSource>> {
+    vec![
+        Box::new(xing::scraper::XingSource::default()),
+        Box::new(linkedin::LinkedinSource::default()),
+        Box::new(instaffo::InstaffoSource::default()),
+        Box::new(stepstone::StepstoneSource),
+        Box::new(glassdoor::GlassdoorSource),
+        Box::new(indeed::IndeedSource::default()),
+        Box::new(jsonld::JsonLdSource::default()),
+        Box::new(greenhouse::GreenhouseSource::default()),
+        Box::new(lever::LeverSource::default()),
+        Box::new(personio::PersonioSource::default()),
+        Box::new(arbeitsagentur::ArbeitsagenturSource::default()),
+    ]
+}
+
+/// Looks up a source in the registry by its name, case insensitive
+pub fn find(name: &str) -> Option<Box<dyn JobSource>> {
+    registry()
+        .into_iter()
+        .find(|source| source.name().eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_registry_names_are_unique() {
+        let names = registry()
+            .iter()
+            .map(|source| source.name())
+            .collect::<Vec<_>>();
+        let unique = names.iter().collect::<HashSet<_>>();
+        assert_eq!(names.len(), unique.len());
+        assert!(find("Xing").is_some());
+        assert!(find("monster").is_none());
+    }
+
+    #[test]
+    fn test_base_url_from_env() {
+        let default = "https://www.example.com";
+        assert_eq!(base_url("TEST_SOURCE_BASE_URL", default), default);
+        std::env::set_var("TEST_SOURCE_BASE_URL", "http://127.0.0.1:8900/");
+        assert_eq!(
+            base_url("TEST_SOURCE_BASE_URL", default),
+            "http://127.0.0.1:8900"
+        );
+    }
+
+    #[test]
+    fn test_env_list() {
+        assert!(env_list("TEST_SOURCE_LIST").is_empty());
+        std::env::set_var("TEST_SOURCE_LIST", " acme, ,mock-gmbh,");
+        assert_eq!(env_list("TEST_SOURCE_LIST"), vec!["acme", "mock-gmbh"]);
+    }
+}
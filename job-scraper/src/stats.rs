@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+use reqwest::StatusCode;
+
+/// What the scrapers fetched since the stats were last taken
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Counts {
+    /// Responses with a success status
+    pub pages: u32,
+    /// Error responses by status, requests that were retried are counted as well
+    pub http_errors: BTreeMap<u16, u32>,
+    /// Postings whose content couldn't be extracted from their page
+    pub extraction_failures: u32,
+}
+
+/// Counters every client reports its responses to
+#[derive(Default)]
+pub struct RunStats {
+    counts: Mutex<Counts>,
+}
+
+impl RunStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record(&self, status: StatusCode) {
+        let mut counts = self.counts.lock().unwrap();
+        if status.is_success() {
+            counts.pages += 1;
+        } else if status.is_client_error() || status.is_server_error() {
+            *counts.http_errors.entry(status.as_u16()).or_default() += 1;
+        }
+    }
+
+    pub(crate) fn extraction_failed(&self) {
+        self.counts.lock().unwrap().extraction_failures += 1;
+    }
+
+    /// Counts since the last call, the next run starts from zero
+    pub fn take(&self) -> Counts {
+        std::mem::take(&mut *self.counts.lock().unwrap())
+    }
+}
+
+lazy_static! {
+    static ref SHARED: Arc<RunStats> = Arc::new(RunStats::new());
+}
+
+/// Stats of every client that doesn't bring its own, sites scraped one after another
+/// take them after each site
+pub fn shared() -> Arc<RunStats> {
+    SHARED.clone()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_take_resets_the_counts() {
+        let stats = RunStats::new();
+        stats.record(StatusCode::OK);
+        stats.record(StatusCode::OK);
+        stats.record(StatusCode::TOO_MANY_REQUESTS);
+        stats.record(StatusCode::NOT_FOUND);
+        stats.record(StatusCode::TOO_MANY_REQUESTS);
+        stats.record(StatusCode::FOUND);
+        stats.extraction_failed();
+        assert_eq!(
+            stats.take(),
+            Counts {
+                pages: 2,
+                http_errors: BTreeMap::from([(404, 1), (429, 2)]),
+                extraction_failures: 1,
+            }
+        );
+        assert_eq!(stats.take(), Counts::default());
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::{Stream, StreamExt};
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::Url;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::client::{Client, RateLimit};
+use crate::headers;
+use crate::query::{url_with_params, SearchQuery};
+use crate::source::{JobSource, JobStream, SearchParams};
+
+type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Request error: '{0}'")]
+    Request(#[from] reqwest::Error),
+    #[error("Failed to scrape data from: '{0}'")]
+    RequestNotOk(String),
+    #[error("Content not found in html: '{0}'")]
+    ContentNotFound(&'static str),
+}
+
+/// Stepstone stops serving result pages after this many pages for a single search
+const MAX_PAGES: u32 = 40;
+
+/// Search pages and job ads share one limit, roughly the pace of a person clicking through
+const RATE_LIMIT: RateLimit = RateLimit {
+    requests_per_second: 1.0,
+    burst: 2,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Job {
+    pub stepstone_id: String,
+    pub title: Option<String>,
+    pub company: Option<String>,
+    location: Option<String>,
+    link: String,
+    posting_date: Option<chrono::DateTime<Utc>>,
+}
+
+/// Stepstone takes keywords and location as path segments, the employment type
+/// can't be filtered through the url and is ignored
+fn job_search_url(query: &SearchQuery, page: u32) -> String {
+    let mut url = Url::parse("https://www.stepstone.de/jobs").unwrap();
+    {
+        let mut segments = url.path_segments_mut().unwrap();
+        segments.push(&query.keywords);
+        if let Some(location) = &query.location {
+            segments.push(&format!("in-{}", location));
+        }
+    }
+    let mut params = vec![("page", page.to_string())];
+    if let Some(radius) = query.radius {
+        params.push(("radius", radius.to_string()));
+    }
+    if let Some(days) = query.recency.and_then(|recency| recency.days()) {
+        params.push(("ag", format!("age_{}", days)));
+    }
+    if query.remote {
+        params.push(("wfh", "1".to_owned()));
+    }
+    url_with_params(url.as_str(), params)
+}
+
+fn extract_job_id(job_url: &str) -> Option<String> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"--(\d+)-inline\.html").unwrap();
+    }
+    RE.captures(job_url)?.get(1).map(|m| m.as_str().to_owned())
+}
+
+fn absolute_url(href: &str) -> String {
+    if href.starts_with("http") {
+        href.to_owned()
+    } else {
+        format!("https://www.stepstone.de{}", href)
+    }
+}
+
+/// Parses the job cards of a search result page, the raw data is filled in later
+/// from the detail page
+fn parse_search_page(body: &str) -> Vec<Job> {
+    let doc = Html::parse_document(body);
+    let item_selector = Selector::parse(r#"article[data-at="job-item"]"#).unwrap();
+    let title_selector = Selector::parse(r#"a[data-at="job-item-title"]"#).unwrap();
+    let company_selector = Selector::parse(r#"[data-at="job-item-company-name"]"#).unwrap();
+    let location_selector = Selector::parse(r#"[data-at="job-item-location"]"#).unwrap();
+    let date_selector = Selector::parse("time").unwrap();
+    doc.select(&item_selector)
+        .filter_map(|item| {
+            let title_el = item.select(&title_selector).next()?;
+            let link = absolute_url(title_el.value().attr("href")?);
+            let stepstone_id = extract_job_id(&link)?;
+            let title = Some(title_el.text().map(str::trim).collect::<String>());
+            let company = item
+                .select(&company_selector)
+                .next()
+                .map(|el| el.text().map(str::trim).collect::<String>());
+            let location = item
+                .select(&location_selector)
+                .next()
+                .map(|el| el.text().map(str::trim).collect::<String>());
+            let posting_date = item
+                .select(&date_selector)
+                .next()
+                .and_then(|el| el.value().attr("datetime"))
+                .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
+                .map(|date| date.with_timezone(&Utc));
+            Some(Job {
+                stepstone_id,
+                title,
+                company,
+                location,
+                link,
+                posting_date,
+            })
+        })
+        .collect()
+}
+
+fn parse_job_content(body: &str) -> Result<String> {
+    let doc = Html::parse_document(body);
+    let content_selector = Selector::parse(r#"[data-at="job-ad-content"]"#).unwrap();
+    let raw_data = doc
+        .select(&content_selector)
+        .next()
+        .ok_or(Error::ContentNotFound("Job Posting data"))?
+        .text()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(raw_data)
+}
+
+async fn scrape_search_page(client: &Client, query: &SearchQuery, page: u32) -> Result<Vec<Job>> {
+    let url = job_search_url(query, page);
+    log::debug!(
+        "requesting jobs from stepstone, page: {}, search: {}",
+        page,
+        query.keywords
+    );
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        log::error!(
+            "failed to retrieve results for page: {}, search: {}, status: {}",
+            page,
+            query.keywords,
+            resp.status()
+        );
+        return Err(Error::RequestNotOk(url));
+    }
+    let body = resp.text().await?;
+    Ok(parse_search_page(&body))
+}
+
+async fn scrape_job_content(client: &Client, job_url: &str) -> Result<String> {
+    let resp = client.get(job_url).send().await?;
+    if !resp.status().is_success() {
+        return Err(Error::RequestNotOk(job_url.to_owned()));
+    }
+    let body = resp.text().await?;
+    parse_job_content(&body)
+}
+
+/// scrape the raw data from the job posting page
+/// then convert it to a crate::Job
+async fn convert(client: &Client, job: Job) -> crate::Job {
+    let raw_data = match scrape_job_content(client, &job.link).await {
+        Ok(content) => {
+            log::debug!("scraped job content for {}", job.link);
+            Some(content)
+        }
+        Err(e) => {
+            log::error!(
+                "failed to scrape job content for url: {}, error: {}",
+                job.link,
+                e
+            );
+            client.extraction_failed();
+            None
+        }
+    };
+    crate::Job::Stepstone {
+        job: Box::new(job),
+        raw_data,
+    }
+}
+
+/// Scrape all jobs for given queries, pages through the search results of every query
+/// and fetches the detail page of each posting that isn't in `known_ids`
+pub async fn scrape(
+    queries: Vec<SearchQuery>,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = crate::Job> {
+    let client = Client::builder(reqwest::Client::new())
+        .profile(headers::random())
+        .default_rate_limit(RATE_LIMIT)
+        .build();
+    let mut scraped_ids: HashSet<String> = known_ids;
+    async_stream::stream! {
+        for query in queries {
+            for page in 1..=MAX_PAGES {
+                let jobs = match scrape_search_page(&client, &query, page).await {
+                    Ok(jobs) => jobs,
+                    Err(e) => {
+                        log::error!("Stopping search for query: {}, error: {}", query.keywords, e);
+                        break;
+                    }
+                };
+                if jobs.is_empty() {
+                    log::info!("No more results for query: {} after page: {}", query.keywords, page - 1);
+                    break;
+                }
+                for job in jobs {
+                    if !scraped_ids.insert(job.stepstone_id.clone()) {
+                        continue;
+                    }
+                    yield convert(&client, job).await;
+                }
+            }
+        }
+    }
+}
+
+pub struct StepstoneSource;
+
+#[async_trait]
+impl JobSource for StepstoneSource {
+    fn name(&self) -> &'static str {
+        "stepstone"
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        scrape(params.queries, params.known_ids)
+            .await
+            .map(Ok)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_job_id() {
+        let url = "https://www.stepstone.de/stellenangebote--Software-Engineer-Berlin-ACME-GmbH--9876543-inline.html";
+        assert_eq!(extract_job_id(url), Some("9876543".to_owned()));
+        assert_eq!(extract_job_id("https://www.stepstone.de/jobs/rust"), None);
+    }
+
+    #[test]
+    fn test_job_search_url() {
+        let mut query = SearchQuery::new("C#");
+        query.location = Some("München".to_owned());
+        query.remote = true;
+        assert_eq!(
+            job_search_url(&query, 2),
+            "https://www.stepstone.de/jobs/C%23/in-M%C3%BCnchen?page=2&wfh=1"
+        );
+    }
+
+    #[test]
+    fn test_parse_search_page() {
+        let body = r#"
+            <html><body>
+            <article data-at="job-item" id="job-item-9876543">
+                <a data-at="job-item-title" href="/stellenangebote--Rust-Developer-Berlin-ACME-GmbH--9876543-inline.html">Rust Developer</a>
+                <span data-at="job-item-company-name">ACME GmbH</span>
+                <span data-at="job-item-location">Berlin</span>
+                <time datetime="2023-05-02T10:00:00+02:00">vor 2 Tagen</time>
+            </article>
+            <article data-at="job-item"><span>Advertisement</span></article>
+            </body></html>
+        "#;
+        let jobs = parse_search_page(body);
+        assert_eq!(jobs.len(), 1);
+        let job = &jobs[0];
+        assert_eq!(job.stepstone_id, "9876543");
+        assert_eq!(job.title.as_deref(), Some("Rust Developer"));
+        assert_eq!(job.company.as_deref(), Some("ACME GmbH"));
+        assert!(job
+            .link
+            .starts_with("https://www.stepstone.de/stellenangebote--"));
+        assert!(job.posting_date.is_some());
+    }
+
+    #[test]
+    fn test_parse_job_content_keeps_words_apart() {
+        let body = r#"
+            <html><body>
+            <div data-at="job-ad-content">
+                <h4>Deine Aufgaben</h4><p>Du baust <b>Services</b> in Rust.</p>
+                <h4>Dein Profil</h4><ul><li>Tokio</li><li>PostgreSQL</li></ul>
+            </div>
+            </body></html>
+        "#;
+        assert_eq!(
+            parse_job_content(body).unwrap(),
+            "Deine Aufgaben Du baust Services in Rust. Dein Profil Tokio PostgreSQL"
+        );
+        assert!(parse_job_content("<html><body></body></html>").is_err());
+    }
+}
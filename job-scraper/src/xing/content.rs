@@ -0,0 +1,241 @@
+use lazy_static::lazy_static;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::Value;
+
+use crate::jsonld::{is_posting, salary_text};
+use crate::markdown;
+use crate::xing::{Error, Result};
+
+/// Extra selectors for the posting content separated by `;`, tried before the built in ones
+pub const SELECTORS_ENV: &str = "XING_CONTENT_SELECTORS";
+
+/// Tried in order, the hashed class names change with every deploy of xing
+const CONTENT_SELECTORS: [&str; 4] = [
+    ".styles-grid-gridContainer-cec162b7.styles-grid-standardGridContainer-cfa898d5",
+    r#"[class*="styles-grid-standardGridContainer"]"#,
+    r#"[data-testid="expanded-description"]"#,
+    "main",
+];
+const SALARY_SELECTORS: [&str; 2] = [
+    r#"[data-cy="posting-salary"]"#,
+    r#"[data-testid="salary-info"]"#,
+];
+
+fn parse_selectors<'a>(selectors: impl Iterator<Item = &'a str>) -> Vec<Selector> {
+    selectors
+        .filter_map(|selector| match Selector::parse(selector) {
+            Ok(selector) => Some(selector),
+            Err(e) => {
+                log::warn!("Ignoring invalid selector '{}': {:?}", selector, e);
+                None
+            }
+        })
+        .collect()
+}
+
+lazy_static! {
+    static ref SELECTORS: Vec<Selector> = {
+        let configured = std::env::var(SELECTORS_ENV).unwrap_or_default();
+        let configured = configured
+            .split(';')
+            .map(str::trim)
+            .filter(|selector| !selector.is_empty());
+        parse_selectors(configured.chain(CONTENT_SELECTORS))
+    };
+    static ref SALARY: Vec<Selector> = parse_selectors(SALARY_SELECTORS.into_iter());
+}
+
+fn html_text(html: &str) -> String {
+    Html::parse_fragment(html)
+        .root_element()
+        .text()
+        .collect::<String>()
+}
+
+/// Keys the hydration state keeps the posting under
+const POSTING_KEYS: [&str; 3] = ["posting", "jobPosting", "jobDetails"];
+
+/// A posting in hydration state has an id next to its title and description,
+/// meta blocks for the page head only have the latter two
+fn is_hydrated_posting(value: &Value) -> bool {
+    value
+        .get("id")
+        .is_some_and(|id| id.is_string() || id.is_number())
+        && value["title"].is_string()
+        && value["description"].is_string()
+}
+
+/// First object describing the posting, either typed as a schema.org `JobPosting`
+/// or, in hydration state, the posting under one of `POSTING_KEYS`
+fn find_posting(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Object(object) => {
+            if is_posting(value) {
+                return Some(value);
+            }
+            let hydrated = POSTING_KEYS
+                .iter()
+                .filter_map(|key| object.get(*key))
+                .find(|posting| is_hydrated_posting(posting));
+            hydrated.or_else(|| object.values().find_map(find_posting))
+        }
+        Value::Array(values) => values.iter().find_map(find_posting),
+        _ => None,
+    }
+}
+
+/// Text of a posting page and its description as Markdown
+#[derive(Debug)]
+pub(crate) struct Content {
+    pub(crate) text: String,
+    pub(crate) markdown: String,
+}
+
+fn join(parts: impl IntoIterator<Item = Option<String>>, separator: &str) -> String {
+    parts
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn posting_content(posting: &Value) -> Option<Content> {
+    let description = posting["description"].as_str()?;
+    let salary = salary_text(&posting["baseSalary"]);
+    let title = posting["title"].as_str().map(str::to_owned);
+    let company = posting["hiringOrganization"]["name"]
+        .as_str()
+        .map(str::to_owned);
+    Some(Content {
+        text: join(
+            [
+                salary.clone(),
+                title.clone(),
+                company.clone(),
+                Some(html_text(description)),
+            ],
+            "\n",
+        ),
+        markdown: join(
+            [
+                title.map(|title| format!("# {}", title)),
+                company,
+                salary,
+                Some(markdown::from_html(description)),
+            ],
+            "\n\n",
+        ),
+    })
+}
+
+fn structured_data(doc: &Html) -> Option<Content> {
+    let scripts =
+        Selector::parse(r#"script[type="application/ld+json"], script#__NEXT_DATA__"#).unwrap();
+    doc.select(&scripts)
+        .filter_map(|script| serde_json::from_str::<Value>(&script.text().collect::<String>()).ok())
+        .find_map(|json| find_posting(&json).and_then(posting_content))
+}
+
+fn first_element<'a>(doc: &'a Html, selectors: &[Selector]) -> Option<ElementRef<'a>> {
+    selectors
+        .iter()
+        .filter_map(|selector| doc.select(selector).next())
+        .find(|element| element.text().any(|text| !text.trim().is_empty()))
+}
+
+/// Content of a posting page, embedded structured data is preferred over the html.
+/// The salary is only part of it if the page shows one
+pub(crate) fn extract(html: &str) -> Result<Content> {
+    let doc = Html::parse_document(html);
+    if let Some(content) = structured_data(&doc) {
+        return Ok(content);
+    }
+    let element =
+        first_element(&doc, &SELECTORS).ok_or(Error::ContentNotFound("Job Posting data"))?;
+    let salary = first_element(&doc, &SALARY);
+    let text = |element: ElementRef| element.text().collect::<String>();
+    Ok(Content {
+        text: join([salary.map(text), Some(text(element))], ""),
+        markdown: join(
+            [
+                salary.map(markdown::from_element),
+                Some(markdown::from_element(element)),
+            ],
+            "\n\n",
+        ),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_prefers_json_ld() {
+        let html = r#"<html><head><script type="application/ld+json">
+        {"@context": "https://schema.org", "@graph": [{"@type": "Organization", "name": "DATEV eG"}, {
+            "@type": "JobPosting",
+            "title": "Anwendungsentwickler Java (m/w/d)",
+            "description": "<p>Du entwickelst <b>Java</b>-Anwendungen.</p>",
+            "hiringOrganization": {"@type": "Organization", "name": "DATEV eG"},
+            "baseSalary": {"@type": "MonetaryAmount", "currency": "EUR",
+                "value": {"@type": "QuantitativeValue", "minValue": 45000, "maxValue": 60000, "unitText": "YEAR"}}
+        }]}
+        </script></head><body><main>Navigation only</main></body></html>"#;
+        let content = extract(html).unwrap();
+        assert_eq!(
+            content.text,
+            "45000 – 60000 EUR per year\nAnwendungsentwickler Java (m/w/d)\nDATEV eG\nDu entwickelst Java-Anwendungen."
+        );
+        assert_eq!(
+            content.markdown,
+            "# Anwendungsentwickler Java (m/w/d)\n\nDATEV eG\n\n45000 – 60000 EUR per year\n\nDu entwickelst **Java**-Anwendungen."
+        );
+    }
+
+    #[test]
+    fn test_hydration_state() {
+        let html = r#"<html><body><main>Loading</main><script id="__NEXT_DATA__" type="application/json">
+        {"props": {"pageProps": {"posting": {"id": 1, "title": "Rust Engineer", "description": "Backend services in Rust"}}}}
+        </script></body></html>"#;
+        assert_eq!(
+            extract(html).unwrap().text,
+            "Rust Engineer\nBackend services in Rust"
+        );
+    }
+
+    #[test]
+    fn test_hydration_state_ignores_meta_blocks() {
+        let html = r#"<html><body><main>Rust Engineer at Mock GmbH</main><script id="__NEXT_DATA__" type="application/json">
+        {"props": {"pageProps": {
+            "meta": {"title": "Jobs bei XING", "description": "Finde deinen Traumjob"},
+            "posting": {"id": "1.mock", "title": "Rust Engineer", "description": "<p>Backend services in Rust</p>"}
+        }}}
+        </script></body></html>"#;
+        assert_eq!(
+            extract(html).unwrap().text,
+            "Rust Engineer\nBackend services in Rust"
+        );
+
+        let meta_only = r#"<html><body><main>Rust Engineer at Mock GmbH</main><script id="__NEXT_DATA__" type="application/json">
+        {"props": {"pageProps": {"seo": {"title": "Jobs bei XING", "description": "Finde deinen Traumjob"}}}}
+        </script></body></html>"#;
+        assert_eq!(
+            extract(meta_only).unwrap().text,
+            "Rust Engineer at Mock GmbH"
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_selectors_without_salary() {
+        let html = r#"<html><body><main><div class="styles-grid-standardGridContainer-0a1b2c3d">
+        <h1>Rust Engineer</h1></div></main></body></html>"#;
+        let content = extract(html).unwrap();
+        assert_eq!(content.text.trim(), "Rust Engineer");
+        assert_eq!(content.markdown, "# Rust Engineer");
+        assert!(matches!(
+            extract("<html><body></body></html>"),
+            Err(Error::ContentNotFound(_))
+        ));
+    }
+}
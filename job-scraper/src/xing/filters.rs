@@ -0,0 +1,135 @@
+use crate::query::{EmploymentType, SearchQuery};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CareerLevel {
+    Student,
+    EntryLevel,
+    Professional,
+    Manager,
+    Executive,
+    SeniorExecutive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemoteOption {
+    FullRemote,
+    PartlyRemote,
+    NonRemote,
+}
+
+/// Xing specific search filters, applied on top of a `SearchQuery`.
+/// Empty lists don't restrict the search
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filters {
+    pub employment_types: Vec<EmploymentType>,
+    pub career_levels: Vec<CareerLevel>,
+    pub remote_options: Vec<RemoteOption>,
+}
+
+fn employment_type_param(employment_type: EmploymentType) -> &'static str {
+    match employment_type {
+        EmploymentType::FullTime => "FULL_TIME.ef2fe9",
+        EmploymentType::PartTime => "PART_TIME.58889d",
+        EmploymentType::Freelance => "CONTRACTOR.6ad77b",
+        EmploymentType::Internship => "INTERN.a4ee4a",
+        EmploymentType::Temporary => "TEMPORARY.c1a3fe",
+    }
+}
+
+fn career_level_param(career_level: CareerLevel) -> &'static str {
+    match career_level {
+        CareerLevel::Student => "1.795d28",
+        CareerLevel::EntryLevel => "2.24d1f6",
+        CareerLevel::Professional => "3.a2a74c",
+        CareerLevel::Manager => "4.6b5ea6",
+        CareerLevel::Executive => "5.0e7b6d",
+        CareerLevel::SeniorExecutive => "6.8d1c4d",
+    }
+}
+
+fn remote_option_param(remote_option: RemoteOption) -> &'static str {
+    match remote_option {
+        RemoteOption::FullRemote => "FULL_REMOTE.050e26",
+        RemoteOption::PartlyRemote => "PARTLY_REMOTE.dbeba4",
+        RemoteOption::NonRemote => "NON_REMOTE.10a1c8",
+    }
+}
+
+fn join<T: Copy + PartialEq>(values: &[T], param: fn(T) -> &'static str) -> Option<String> {
+    let mut unique: Vec<T> = Vec::with_capacity(values.len());
+    for value in values {
+        if !unique.contains(value) {
+            unique.push(*value);
+        }
+    }
+    if unique.is_empty() {
+        return None;
+    }
+    Some(unique.into_iter().map(param).collect::<Vec<_>>().join(","))
+}
+
+impl Filters {
+    /// Url parameters for the search api, combining the filters with the generic query fields
+    pub(crate) fn params(&self, query: &SearchQuery) -> Vec<(&'static str, String)> {
+        let mut params = vec![("keywords", query.keywords.clone())];
+        if let Some(location) = &query.location {
+            params.push(("location", location.clone()));
+        }
+        if let Some(radius) = query.radius {
+            params.push(("radius", radius.to_string()));
+        }
+        let employment_types = query
+            .employment_type
+            .into_iter()
+            .chain(self.employment_types.iter().copied())
+            .collect::<Vec<_>>();
+        if let Some(value) = join(&employment_types, employment_type_param) {
+            params.push(("employmentType", value));
+        }
+        if let Some(value) = join(&self.career_levels, career_level_param) {
+            params.push(("careerLevel", value));
+        }
+        let remote_options = query
+            .remote
+            .then_some(RemoteOption::FullRemote)
+            .into_iter()
+            .chain(self.remote_options.iter().copied())
+            .collect::<Vec<_>>();
+        if let Some(value) = join(&remote_options, remote_option_param) {
+            params.push(("remoteOption", value));
+        }
+        params
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_params_merge_query_and_filters() {
+        let query = SearchQuery {
+            employment_type: Some(EmploymentType::FullTime),
+            remote: true,
+            ..SearchQuery::new("Rust")
+        };
+        let filters = Filters {
+            employment_types: vec![EmploymentType::FullTime, EmploymentType::Freelance],
+            career_levels: vec![CareerLevel::Professional],
+            remote_options: vec![],
+        };
+        let params = filters.params(&query);
+        assert!(params.contains(&(
+            "employmentType",
+            "FULL_TIME.ef2fe9,CONTRACTOR.6ad77b".to_owned()
+        )));
+        assert!(params.contains(&("careerLevel", "3.a2a74c".to_owned())));
+        assert!(params.contains(&("remoteOption", "FULL_REMOTE.050e26".to_owned())));
+    }
+
+    #[test]
+    fn test_default_filters_dont_restrict_employment_type() {
+        let params = Filters::default().params(&SearchQuery::new("Rust"));
+        assert_eq!(params, vec![("keywords", "Rust".to_owned())]);
+    }
+}
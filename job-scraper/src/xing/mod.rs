@@ -1,3 +1,6 @@
+mod content;
+pub mod filters;
+mod partition;
 pub mod scraper;
 pub(crate) mod types;
 
@@ -0,0 +1,192 @@
+use crate::query::{EmploymentType, SearchQuery};
+use crate::xing::filters::{CareerLevel, Filters};
+
+/// Xing never serves more than this many results for a single search
+pub(crate) const MAX_RESULTS: u32 = 1000;
+
+/// Radius in km used around each city when a search is split by location
+const CITY_RADIUS: u32 = 50;
+
+const EMPLOYMENT_TYPES: [EmploymentType; 5] = [
+    EmploymentType::FullTime,
+    EmploymentType::PartTime,
+    EmploymentType::Freelance,
+    EmploymentType::Internship,
+    EmploymentType::Temporary,
+];
+
+const CAREER_LEVELS: [CareerLevel; 6] = [
+    CareerLevel::Student,
+    CareerLevel::EntryLevel,
+    CareerLevel::Professional,
+    CareerLevel::Manager,
+    CareerLevel::Executive,
+    CareerLevel::SeniorExecutive,
+];
+
+/// Largest job markets in Germany. Postings outside of the radius around these cities
+/// are only found among the capped results of the search that was split by location
+const CITIES: [&str; 15] = [
+    "Berlin",
+    "Hamburg",
+    "München",
+    "Köln",
+    "Frankfurt am Main",
+    "Stuttgart",
+    "Düsseldorf",
+    "Leipzig",
+    "Dortmund",
+    "Bremen",
+    "Dresden",
+    "Hannover",
+    "Nürnberg",
+    "Karlsruhe",
+    "Münster",
+];
+
+/// Locations that cover all of `CITIES`, searches in these are split like searches without one
+const COUNTRIES: [&str; 3] = ["germany", "deutschland", "de"];
+
+fn is_country(location: Option<&str>) -> bool {
+    location.is_none_or(|location| COUNTRIES.contains(&location.trim().to_lowercase().as_str()))
+}
+
+/// Splits a search that exceeds `MAX_RESULTS` into narrower searches along the first
+/// dimension that isn't restricted yet: employment type, then career level, then city
+/// if the search covers all of Germany.
+/// Returns `None` once the search can't be narrowed down any further
+pub(crate) fn split(query: &SearchQuery, filters: &Filters) -> Option<Vec<(SearchQuery, Filters)>> {
+    let mut employment_types: Vec<EmploymentType> = Vec::new();
+    for employment_type in query
+        .employment_type
+        .into_iter()
+        .chain(filters.employment_types.iter().copied())
+    {
+        if !employment_types.contains(&employment_type) {
+            employment_types.push(employment_type);
+        }
+    }
+    if employment_types.len() != 1 {
+        if employment_types.is_empty() {
+            employment_types = EMPLOYMENT_TYPES.to_vec();
+        }
+        let partitions = employment_types
+            .into_iter()
+            .map(|employment_type| {
+                let query = SearchQuery {
+                    employment_type: None,
+                    ..query.clone()
+                };
+                let filters = Filters {
+                    employment_types: vec![employment_type],
+                    ..filters.clone()
+                };
+                (query, filters)
+            })
+            .collect();
+        return Some(partitions);
+    }
+
+    if filters.career_levels.len() != 1 {
+        let career_levels = if filters.career_levels.is_empty() {
+            CAREER_LEVELS.to_vec()
+        } else {
+            filters.career_levels.clone()
+        };
+        let partitions = career_levels
+            .into_iter()
+            .map(|career_level| {
+                let filters = Filters {
+                    career_levels: vec![career_level],
+                    ..filters.clone()
+                };
+                (query.clone(), filters)
+            })
+            .collect();
+        return Some(partitions);
+    }
+
+    if is_country(query.location.as_deref()) {
+        let partitions = CITIES
+            .iter()
+            .map(|city| {
+                let query = SearchQuery {
+                    location: Some(city.to_string()),
+                    radius: query.radius.or(Some(CITY_RADIUS)),
+                    ..query.clone()
+                };
+                (query, filters.clone())
+            })
+            .collect();
+        return Some(partitions);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_order() {
+        let query = SearchQuery::new("Software Engineer");
+        let filters = Filters::default();
+
+        let by_employment_type = split(&query, &filters).expect("Should split");
+        assert_eq!(by_employment_type.len(), EMPLOYMENT_TYPES.len());
+
+        let (query, filters) = by_employment_type[0].clone();
+        let by_career_level = split(&query, &filters).expect("Should split");
+        assert_eq!(by_career_level.len(), CAREER_LEVELS.len());
+
+        let (query, filters) = by_career_level[0].clone();
+        let by_city = split(&query, &filters).expect("Should split");
+        assert_eq!(by_city.len(), CITIES.len());
+        assert_eq!(by_city[0].0.radius, Some(CITY_RADIUS));
+
+        let (query, filters) = by_city[0].clone();
+        assert!(split(&query, &filters).is_none());
+    }
+
+    #[test]
+    fn test_country_is_split_by_city() {
+        let query = SearchQuery {
+            location: Some("Germany".to_owned()),
+            employment_type: Some(EmploymentType::FullTime),
+            ..SearchQuery::new("Software Engineer")
+        };
+        let filters = Filters {
+            career_levels: vec![CareerLevel::Professional],
+            ..Default::default()
+        };
+        let by_city = split(&query, &filters).expect("Should split");
+        assert_eq!(by_city.len(), CITIES.len());
+        assert_eq!(by_city[0].0.location.as_deref(), Some("Berlin"));
+
+        let in_city = SearchQuery {
+            location: Some("Berlin".to_owned()),
+            ..query
+        };
+        assert!(split(&in_city, &filters).is_none());
+    }
+
+    #[test]
+    fn test_split_keeps_selected_employment_types() {
+        let filters = Filters {
+            employment_types: vec![EmploymentType::PartTime, EmploymentType::Freelance],
+            ..Default::default()
+        };
+        let partitions = split(&SearchQuery::new("Rust"), &filters).expect("Should split");
+        let employment_types = partitions
+            .into_iter()
+            .map(|(_, filters)| filters.employment_types)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            employment_types,
+            vec![
+                vec![EmploymentType::PartTime],
+                vec![EmploymentType::Freelance]
+            ]
+        );
+    }
+}
@@ -1,20 +1,35 @@
 use async_stream::stream;
-use futures::Stream;
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 use std::cmp::min;
 use std::collections::HashSet;
 use std::future::Future;
 
 use log;
-use reqwest::Client;
 
-use scraper::Html;
-use scraper::Selector;
+use reqwest::header::{HeaderValue, ACCEPT};
 use serde::{Deserialize, Serialize};
 
+use crate::client::{Client, RateLimit};
+use crate::headers::{self, Destination};
+use crate::query::{url_with_params, SearchQuery};
+use crate::source::{base_url, JobSource, JobStream, SearchParams};
+use crate::xing::content;
+use crate::xing::filters::Filters;
+use crate::xing::partition::{split, MAX_RESULTS};
 use crate::xing::types::Job;
 use crate::xing::Error;
 use crate::xing::Result;
 
+/// Live site, `XING_BASE_URL` points the scraper somewhere else
+pub const BASE_URL: &str = "https://www.xing.com";
+
+/// The api copes with many parallel requests, this only keeps the workers from flooding it
+const RATE_LIMIT: RateLimit = RateLimit {
+    requests_per_second: 10.0,
+    burst: 20,
+};
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct MetaData {
@@ -31,28 +46,39 @@ struct ApiResponse {
     meta: MetaData,
 }
 
-fn job_search_url(offset: u32, results: u32, search: &str) -> String {
-    format!(
-        "https://www.xing.com/jobs/api/search?employmentType=FULL_TIME.ef2fe9&offset={}&limit={}&keywords={}",
-        offset, results, search
-    )
+fn job_search_url(
+    base_url: &str,
+    offset: u32,
+    results: u32,
+    search: &SearchQuery,
+    filters: &Filters,
+) -> String {
+    let mut params = vec![
+        ("offset", offset.to_string()),
+        ("limit", results.to_string()),
+    ];
+    params.extend(filters.params(search));
+    url_with_params(&format!("{}/jobs/api/search", base_url), params)
 }
 
 async fn scrape_job_search_page(
     client: &Client,
+    base_url: &str,
     offset: u32,
     results: u32,
-    search: &str,
+    search: &SearchQuery,
+    filters: &Filters,
 ) -> Result<ApiResponse> {
-    let url = job_search_url(offset, results, search);
+    let url = job_search_url(base_url, offset, results, search, filters);
     log::debug!(
         "requesting jobs from xing, offset: {}, search: {}",
         offset,
-        search
+        search.keywords
     );
     let resp = client
         .get(&url)
-        .header("Accept", "application/json")
+        .destination(Destination::Fetch)
+        .header(ACCEPT, HeaderValue::from_static("application/json"))
         .send()
         .await?;
     if !resp.status().is_success() {
@@ -60,7 +86,7 @@ async fn scrape_job_search_page(
         log::error!(
             "failed to retrieve results for offset: {}, search: {}, error resp body: {:?}",
             offset,
-            search,
+            search.keywords,
             error_body,
         );
         return Err(Error::RequestNotOk(url));
@@ -69,7 +95,7 @@ async fn scrape_job_search_page(
     log::debug!(
         "successfully retrieved results for offset: {}, search: {}",
         offset,
-        search
+        search.keywords
     );
     let job_search: ApiResponse = resp.json().await?;
     Ok(job_search)
@@ -78,14 +104,24 @@ async fn scrape_job_search_page(
 async fn scrape_job_search_batch(
     start: u32,
     end: u32,
-    search: String,
+    search: SearchQuery,
+    filters: Filters,
     results_per_page: u32,
     client: Client,
+    base_url: String,
 ) -> Vec<Result<ApiResponse>> {
     let mut results = Vec::with_capacity((end - start) as usize);
     for page in start..end {
         let offset = page * results_per_page;
-        let page = scrape_job_search_page(&client, offset, results_per_page, search.as_str()).await;
+        let page = scrape_job_search_page(
+            &client,
+            &base_url,
+            offset,
+            results_per_page,
+            &search,
+            &filters,
+        )
+        .await;
         results.push(page);
     }
     results
@@ -93,12 +129,15 @@ async fn scrape_job_search_batch(
 
 async fn scrape_api(
     client: Client,
-    keyword: String,
+    base_url: String,
+    query: SearchQuery,
+    filters: Filters,
     workers: u32,
 ) -> Result<Vec<Result<ApiResponse>>> {
     let results_per_page = 100;
-    let first_page = scrape_job_search_page(&client, 0, results_per_page, &keyword).await?;
-    let results_count = min(first_page.meta.count, 1000);
+    let first_page =
+        scrape_job_search_page(&client, &base_url, 0, results_per_page, &query, &filters).await?;
+    let results_count = min(first_page.meta.count, MAX_RESULTS);
     let mut results = Vec::with_capacity(results_count as usize);
     let last_page_index = min(first_page.meta.max_page, results_count / results_per_page);
     results.push(Ok(first_page));
@@ -117,9 +156,11 @@ async fn scrape_api(
         let handle = tokio::spawn(scrape_job_search_batch(
             start,
             end,
-            String::from(&keyword),
+            query.clone(),
+            filters.clone(),
             results_per_page,
             client,
+            base_url.clone(),
         ));
         handles.push(handle);
     }
@@ -133,29 +174,151 @@ async fn scrape_api(
     Ok(results)
 }
 
-/// Scrape all jobs for given queries
+async fn count_results(
+    client: &Client,
+    base_url: &str,
+    query: &SearchQuery,
+    filters: &Filters,
+) -> Option<u32> {
+    match scrape_job_search_page(client, base_url, 0, 1, query, filters).await {
+        Ok(page) => Some(page.meta.count),
+        Err(e) => {
+            log::error!(
+                "failed to count results for search: {}, error: {}",
+                query.keywords,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Narrows down a search until every partition fits into the result cap of the api.
+/// A split search whose partitions don't add up to its results is kept as well,
+/// its first `MAX_RESULTS` results might include postings none of the partitions has
+async fn partition(
+    client: &Client,
+    base_url: &str,
+    query: SearchQuery,
+    filters: Filters,
+) -> Vec<(SearchQuery, Filters)> {
+    let mut pending = Vec::new();
+    if let Some(count) = count_results(client, base_url, &query, &filters).await {
+        pending.push((query, filters, count));
+    }
+    let mut partitions = Vec::new();
+    while let Some((query, filters, count)) = pending.pop() {
+        if count <= MAX_RESULTS {
+            partitions.push((query, filters));
+            continue;
+        }
+        let Some(narrower) = split(&query, &filters) else {
+            log::warn!(
+                "search: {} in {} can't be narrowed down further, only {} of {} results are reachable",
+                query.keywords,
+                query.location.as_deref().unwrap_or("Germany"),
+                MAX_RESULTS,
+                count
+            );
+            partitions.push((query, filters));
+            continue;
+        };
+        log::debug!(
+            "search: {} has {} results, splitting into {} partitions",
+            query.keywords,
+            count,
+            narrower.len()
+        );
+        let mut covered = 0;
+        for (query, filters) in narrower {
+            if let Some(count) = count_results(client, base_url, &query, &filters).await {
+                covered += count;
+                pending.push((query, filters, count));
+            }
+        }
+        if covered < count {
+            log::warn!(
+                "partitions of search: {} cover {} of {} results, scraping the unsplit search as well",
+                query.keywords,
+                covered,
+                count
+            );
+            partitions.push((query, filters));
+        }
+    }
+    partitions
+}
+
+/// Scrape every partition of a search
+async fn scrape_partitioned(
+    client: Client,
+    base_url: String,
+    query: SearchQuery,
+    filters: Filters,
+    workers: u32,
+) -> Vec<Result<ApiResponse>> {
+    let mut results = Vec::new();
+    for (query, filters) in partition(&client, &base_url, query, filters).await {
+        match scrape_api(client.clone(), base_url.clone(), query, filters, workers).await {
+            Ok(pages) => results.extend(pages),
+            Err(e) => results.push(Err(e)),
+        }
+    }
+    results
+}
+
+/// Scrape all jobs for given queries, queries exceeding the result cap
+/// of the api are split into narrower searches
 /// Results are buffered into the tokin::fs::File provided
+/// Only postings missing from `known_ids` have their page scraped
 pub async fn scrape_queries(
-    queries: Vec<String>,
+    base_url: String,
+    queries: Vec<SearchQuery>,
+    filters: Filters,
+    known_ids: HashSet<String>,
+) -> impl Stream<Item = impl Future<Output = crate::Job>> {
+    let client = Client::builder(reqwest::Client::default())
+        .profile(headers::random())
+        .rate_limit("www.xing.com", RATE_LIMIT)
+        .build();
+    scrape_queries_with(client, base_url, queries, filters, known_ids).await
+}
+
+async fn scrape_queries_with(
+    client: Client,
+    base_url: String,
+    queries: Vec<SearchQuery>,
+    filters: Filters,
+    known_ids: HashSet<String>,
 ) -> impl Stream<Item = impl Future<Output = crate::Job>> {
     let mut handles = Vec::with_capacity(queries.len());
-    let client = Client::new();
     queries.into_iter().for_each(|query| {
-        let join_handle = tokio::spawn(scrape_api(client.clone(), query, 2));
+        let join_handle = tokio::spawn(scrape_partitioned(
+            client.clone(),
+            base_url.clone(),
+            query,
+            filters.clone(),
+            2,
+        ));
         handles.push(join_handle);
     });
     let results = futures::future::join_all(handles)
         .await
         .into_iter()
         .filter_map(std::result::Result::ok)
-        .filter_map(Result::ok)
         .flatten()
         .filter_map(Result::ok)
         .flat_map(|job_search| job_search.items)
         .collect::<HashSet<_>>();
+    let found = results.len();
+    let results = results
+        .into_iter()
+        .filter(|job| !known_ids.contains(&job.id.to_string()))
+        .collect::<Vec<_>>();
 
     log::info!(
-        "found {} unique jobs, scraping page data for each",
+        "found {} unique jobs, scraping page data for the {} new ones",
+        found,
         results.len()
     );
 
@@ -168,14 +331,48 @@ pub async fn scrape_queries(
     job_stream
 }
 
+pub struct XingSource {
+    pub filters: Filters,
+    pub base_url: String,
+}
+
+impl Default for XingSource {
+    fn default() -> Self {
+        Self {
+            filters: Filters::default(),
+            base_url: base_url("XING_BASE_URL", BASE_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl JobSource for XingSource {
+    fn name(&self) -> &'static str {
+        "xing"
+    }
+
+    async fn jobs(&self, params: SearchParams) -> JobStream {
+        scrape_queries(
+            self.base_url.clone(),
+            params.queries,
+            self.filters.clone(),
+            params.known_ids,
+        )
+        .await
+        .buffer_unordered(500)
+        .map(Ok)
+        .boxed()
+    }
+}
+
 /// scrape the raw data from the job posting page
 /// then convert it to a JobPost
 async fn convert(client: Client, job: Job) -> crate::Job {
-    let job_content = scrape_job_content(client, &job.link).await;
-    let job_content = match job_content {
+    let job_content = scrape_job_content(client.clone(), &job.link).await;
+    let (raw_data, description) = match job_content {
         Ok(content) => {
             log::debug!("scraped job content for {}", job.link);
-            Some(content)
+            (Some(content.text), Some(content.markdown))
         }
         Err(e) => {
             log::error!(
@@ -183,79 +380,126 @@ async fn convert(client: Client, job: Job) -> crate::Job {
                 job.link,
                 e
             );
-            None
+            client.extraction_failed();
+            (None, None)
         }
     };
     crate::Job::Xing {
         job: Box::new(job),
-        raw_data: job_content,
+        raw_data,
+        description,
     }
 }
 
-async fn scrape_job_content(client: Client, job_url: &str) -> Result<String> {
-    let job_url = job_url;
+async fn scrape_job_content(client: Client, job_url: &str) -> Result<content::Content> {
     let resp = client.get(job_url).send().await?;
     let html = resp.text().await?;
-    let doc = Html::parse_document(&html);
-    let job_data_selector = Selector::parse(
-        ".styles-grid-gridContainer-cec162b7.styles-grid-standardGridContainer-cfa898d5",
-    )
-    .unwrap();
-    let job_data = doc
-        .select(&job_data_selector)
-        .next()
-        .ok_or(Error::ContentNotFound("Job Posting data"))?
-        .text();
-
-    let salary_selector = Selector::parse(r#"[data-cy="posting-salary"]"#).unwrap();
-    let salary_data = doc
-        .select(&salary_selector)
-        .next()
-        .ok_or(Error::ContentNotFound("Salary"))?
-        .text();
-    let raw_data = salary_data.chain(job_data).collect::<String>();
-    return Ok(raw_data);
+    content::extract(&html)
 }
 
 // test module
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fixtures::{self, Fixtures};
     use futures::{stream, StreamExt};
     use tokio::pin;
     use tokio::time::Instant;
 
+    fn fixture_client(name: &str) -> Client {
+        Client::builder(reqwest::Client::new())
+            .fixtures(Fixtures::from_env(fixtures::path(name)))
+            .build()
+    }
+
+    #[test]
+    fn test_job_search_url_encodes_keywords() {
+        let url = job_search_url(
+            BASE_URL,
+            0,
+            100,
+            &SearchQuery::new("C++"),
+            &Filters::default(),
+        );
+        assert_eq!(
+            url,
+            "https://www.xing.com/jobs/api/search?offset=0&limit=100&keywords=C%2B%2B"
+        );
+    }
+
     #[tokio::test]
     async fn test_get_and_deserialize_job_search() {
-        let _ = scrape_job_search_page(&Client::new(), 0, 100, "Software")
-            .await
-            .expect("Request failed");
+        let page = scrape_job_search_page(
+            &fixture_client("xing_job_search"),
+            BASE_URL,
+            0,
+            100,
+            &SearchQuery::new("Software"),
+            &Filters::default(),
+        )
+        .await
+        .expect("Request failed");
+        assert_eq!(page.meta.count, 2);
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(
+            page.items[0].link,
+            "https://www.xing.com/jobs/nuernberg-anwendungsentwickler-java-98960724"
+        );
     }
 
     #[tokio::test]
     async fn test_scrape_with_query() {
         let query = "React Frontend Engineer";
-        let results = scrape_api(Client::new(), query.to_owned(), 2).await;
+        let results = scrape_api(
+            fixture_client("xing_scrape_api"),
+            BASE_URL.to_owned(),
+            SearchQuery::new(query),
+            Filters::default(),
+            2,
+        )
+        .await;
         assert!(results.is_ok(), "Failed to scrape with query: {}", query);
     }
 
     #[tokio::test]
     async fn test_scrape_stream_api() {
         env_logger::init();
-        let queries = vec!["Svelte".to_owned(), "Rust".to_owned()];
-        let stream = scrape_queries(queries).await.buffer_unordered(400);
+        let queries = vec![SearchQuery::new("Svelte"), SearchQuery::new("Rust")];
+        let stream = scrape_queries_with(
+            fixture_client("xing_scrape_stream"),
+            BASE_URL.to_owned(),
+            queries,
+            Filters::default(),
+            HashSet::new(),
+        )
+        .await
+        .buffer_unordered(400);
         pin!(stream);
         let mut job_count = 0;
-        while let Some(_) = stream.next().await {
+        while let Some(job) = stream.next().await {
+            assert!(matches!(
+                job,
+                crate::Job::Xing {
+                    raw_data: Some(_),
+                    ..
+                }
+            ));
             job_count += 1;
         }
-        assert!(job_count > 0, "Failed to scrape any jobs");
+        // the first Rust posting is also found for Svelte
+        assert_eq!(job_count, 2, "Failed to scrape all jobs");
     }
 
     #[tokio::test]
     async fn test_parse_html_for_job_posting() {
         let job_url = "https://www.xing.com/jobs/nuernberg-anwendungsentwickler-java-98960724";
-        let data = scrape_job_content(Client::new(), job_url).await;
+        let data = scrape_job_content(fixture_client("xing_job_posting"), job_url).await;
         assert!(data.is_ok(), "Failed to parse html");
+        let data = data.unwrap();
+        assert!(data.text.starts_with("€45,000 – €60,000"));
+        assert!(data.text.contains("Anwendungsentwickler Java"));
+        assert!(data
+            .markdown
+            .starts_with("€45,000 – €60,000\n\n# Anwendungsentwickler Java (m/w/d)\n\nDATEV eG"));
     }
 }
@@ -21,7 +21,7 @@ struct Company {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Job {
-    id: u32,
+    pub(crate) id: u32,
     scrambled_id: String,
     company: Company,
     favorite_posting: Option<String>,
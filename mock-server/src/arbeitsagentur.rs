@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use base64::Engine;
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json::json;
+
+use crate::{json, status, State};
+
+pub(crate) const SEARCH_PATH: &str = "/arbeitsagentur/pc/v4/jobs";
+pub(crate) const DETAILS_PATH: &str = "/arbeitsagentur/pc/v4/jobdetails/";
+
+/// Requests without the key of the agency's web app are rejected like the live api does
+const API_KEY: &str = "jobboerse-jobsuche";
+
+fn refnr(index: u32) -> String {
+    format!("10000-{:010}-S", index)
+}
+
+fn has_api_key(req: &Request<Body>) -> bool {
+    req.headers()
+        .get("x-api-key")
+        .is_some_and(|key| key == API_KEY)
+}
+
+pub(crate) fn search(
+    state: &State,
+    req: &Request<Body>,
+    params: &HashMap<String, String>,
+) -> Response<Body> {
+    if !has_api_key(req) {
+        return status(StatusCode::FORBIDDEN);
+    }
+    let param = |key: &str| params.get(key).and_then(|value| value.parse::<u32>().ok());
+    let page = param("page").unwrap_or(1).max(1);
+    let size = param("size").unwrap_or(25).max(1);
+    let total = state.config.jobs;
+    let start = (page - 1).saturating_mul(size);
+    let listings = (start..total.min(start.saturating_add(size)))
+        .map(|index| {
+            json!({
+                "beruf": "Softwareentwickler/in",
+                "titel": format!("Softwareentwickler {} (m/w/d)", index),
+                "refnr": refnr(index),
+                "arbeitsort": {"plz": "10115", "ort": "Berlin", "region": "Berlin", "land": "Deutschland"},
+                "arbeitgeber": format!("Mock GmbH {}", index % 7),
+                "aktuelleVeroeffentlichungsdatum": "2023-05-02",
+                "eintrittsdatum": "2023-06-01",
+                "modifikationsTimestamp": "2023-05-02T08:00:00.000",
+            })
+        })
+        .collect::<Vec<_>>();
+    let body = json!({
+        "stellenangebote": listings,
+        "maxErgebnisse": total.to_string(),
+        "page": page.to_string(),
+        "size": size.to_string(),
+    });
+    json(body.to_string())
+}
+
+/// Details of the posting whose base64 encoded reference number is `id`
+pub(crate) fn details(state: &State, req: &Request<Body>, id: &str) -> Response<Body> {
+    if !has_api_key(req) {
+        return status(StatusCode::FORBIDDEN);
+    }
+    // the client percent-encodes the id, base64 padding included
+    let index = url::form_urlencoded::parse(format!("id={}", id).as_bytes())
+        .next()
+        .and_then(|(_, id)| {
+            base64::engine::general_purpose::STANDARD
+                .decode(id.as_bytes())
+                .ok()
+        })
+        .and_then(|refnr| String::from_utf8(refnr).ok())
+        .and_then(|refnr| {
+            refnr
+                .strip_prefix("10000-")?
+                .strip_suffix("-S")?
+                .parse::<u32>()
+                .ok()
+        })
+        .filter(|index| *index < state.config.jobs);
+    let index = match index {
+        Some(index) => index,
+        None => return status(StatusCode::NOT_FOUND),
+    };
+    if state.is_malformed(index) {
+        return json(r#"{"refnr":"#.to_owned());
+    }
+    let body = json!({
+        "refnr": refnr(index),
+        "titel": format!("Softwareentwickler {} (m/w/d)", index),
+        "stellenangebotsBeschreibung": "Wir entwickeln Services in Rust.\nDein Profil: Erfahrung mit Tokio.",
+        "arbeitszeitmodelle": ["VOLLZEIT", "HEIMARBEIT"],
+        "verguetung": "nach Vereinbarung",
+    });
+    json(body.to_string())
+}
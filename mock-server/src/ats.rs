@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use hyper::{Body, Response, StatusCode};
+use serde_json::json;
+
+use crate::{json, response, status, State};
+
+pub(crate) const GREENHOUSE_PATH: &str = "/greenhouse/v1/boards/";
+pub(crate) const LEVER_PATH: &str = "/lever/v0/postings/";
+pub(crate) const PERSONIO_PATH: &str = "/personio/";
+
+/// Company without a board, its requests are answered with 404
+const MISSING_COMPANY: &str = "missing";
+
+/// Greenhouse ids are unique across boards, every board gets its own range
+fn greenhouse_id(board: &str, index: u32) -> u64 {
+    4_000_000 + board.len() as u64 * 1000 + index as u64
+}
+
+/// Board of `/greenhouse/v1/boards/<board>/jobs`
+pub(crate) fn greenhouse(state: &State, path: &str) -> Response<Body> {
+    let board = match path.strip_suffix("/jobs") {
+        Some(board) if board != MISSING_COMPANY => board,
+        _ => return status(StatusCode::NOT_FOUND),
+    };
+    let jobs = (0..state.config.jobs)
+        .map(|index| {
+            let id = greenhouse_id(board, index);
+            json!({
+                "id": id,
+                "title": format!("Software Engineer {}", index),
+                "absolute_url": format!("https://boards.greenhouse.io/{}/jobs/{}", board, id),
+                "updated_at": "2023-05-02T10:15:00-04:00",
+                "location": {"name": "Berlin, Germany"},
+                "content": "&lt;p&gt;We build services in &lt;strong&gt;Rust&lt;/strong&gt;.&lt;/p&gt;",
+                "departments": [{"id": 1, "name": "Engineering"}],
+            })
+        })
+        .collect::<Vec<_>>();
+    let body = json!({"jobs": jobs, "meta": {"total": state.config.jobs}});
+    json(body.to_string())
+}
+
+/// Postings of `/lever/v0/postings/<company>`, paged with `skip` and `limit`
+pub(crate) fn lever(
+    state: &State,
+    company: &str,
+    params: &HashMap<String, String>,
+) -> Response<Body> {
+    if company == MISSING_COMPANY {
+        return status(StatusCode::NOT_FOUND);
+    }
+    let param = |key: &str| params.get(key).and_then(|value| value.parse::<u32>().ok());
+    let skip = param("skip").unwrap_or(0);
+    let limit = param("limit").unwrap_or(state.config.jobs).max(1);
+    let postings = (skip..state.config.jobs.min(skip.saturating_add(limit)))
+        .map(|index| {
+            let id = format!("{}-{:08}", company, index);
+            json!({
+                "id": id,
+                "text": format!("Software Engineer {}", index),
+                "hostedUrl": format!("https://jobs.lever.co/{}/{}", company, id),
+                "createdAt": 1683014400000u64,
+                "categories": {"location": "Berlin", "team": "Engineering", "commitment": "Full-time"},
+                "description": "<div>We build services in Rust.</div>",
+                "lists": [{"text": "Requirements", "content": "<li>Rust</li><li>tokio</li>"}],
+                "additional": "",
+                "workplaceType": "hybrid",
+            })
+        })
+        .collect::<Vec<_>>();
+    json(serde_json::Value::from(postings).to_string())
+}
+
+/// Xml feed of `/personio/<company>/xml`
+pub(crate) fn personio(state: &State, path: &str) -> Response<Body> {
+    match path.strip_suffix("/xml") {
+        Some(company) if company != MISSING_COMPANY => {}
+        _ => return status(StatusCode::NOT_FOUND),
+    }
+    let positions = (0..state.config.jobs)
+        .map(|index| {
+            format!(
+                "<position><id>{}</id><office>Munich</office><department>Engineering</department>\
+                 <name>Software Engineer {}</name><jobDescriptions><jobDescription>\
+                 <name>Deine Aufgaben</name><value><![CDATA[<p>Services in <b>Rust</b></p>]]></value>\
+                 </jobDescription></jobDescriptions><employmentType>permanent</employmentType>\
+                 <schedule>full-time</schedule><createdAt>2023-05-02T08:00:00+00:00</createdAt></position>",
+                1_000_000 + index,
+                index
+            )
+        })
+        .collect::<String>();
+    response(
+        "text/xml; charset=utf-8",
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><workzag-jobs>{}</workzag-jobs>"#,
+            positions
+        ),
+    )
+}
@@ -0,0 +1,79 @@
+use hyper::{Body, Response, StatusCode};
+use serde_json::json;
+
+use crate::{html, response, status, State};
+
+pub(crate) const CAREERS_PATH: &str = "/careers";
+pub(crate) const POSTING_PATH: &str = "/careers/";
+pub(crate) const SITEMAP_PATH: &str = "/sitemap.xml";
+
+/// Listing of every posting with links a crawler has to leave alone
+pub(crate) fn careers(state: &State) -> Response<Body> {
+    let links = (0..state.config.jobs)
+        .map(|index| {
+            format!(
+                r#"<li><a href="{}{}">Software Engineer {}</a></li>"#,
+                POSTING_PATH, index, index
+            )
+        })
+        .collect::<String>();
+    html(format!(
+        r##"<html><body><a href="#top">Top</a><h1>Careers at Mock GmbH</h1><ul>{}</ul>
+        <a href="https://www.example.com/about">About</a>
+        <a href="mailto:jobs@example.com">Contact</a>
+        <a href="/files/benefits.pdf">Benefits</a></body></html>"##,
+        links
+    ))
+}
+
+/// Posting page with its `JobPosting` as JSON-LD, malformed pages have none
+pub(crate) fn posting(state: &State, index: &str) -> Response<Body> {
+    let index = match index.parse::<u32>() {
+        Ok(index) if index < state.config.jobs => index,
+        _ => return status(StatusCode::NOT_FOUND),
+    };
+    let title = format!("Software Engineer {}", index);
+    if state.is_malformed(index) {
+        return html(format!(
+            "<html><body><h1>{}</h1><p>Apply now</p></body></html>",
+            title
+        ));
+    }
+    let posting = json!({
+        "@context": "https://schema.org",
+        "@type": "JobPosting",
+        "title": title,
+        "url": format!("{}{}{}", state.url, POSTING_PATH, index),
+        "description": "<p>We build services in <b>Rust</b>.</p><ul><li>tokio</li><li>MongoDB</li></ul>",
+        "datePosted": "2023-05-02",
+        "employmentType": "FULL_TIME",
+        "hiringOrganization": {"@type": "Organization", "name": "Mock GmbH"},
+        "jobLocation": {
+            "@type": "Place",
+            "address": {"@type": "PostalAddress", "addressLocality": "Berlin", "addressCountry": "DE"},
+        },
+    });
+    html(format!(
+        r#"<html><head><script type="application/ld+json">{}</script></head>
+        <body><a href="{}">All jobs</a><h1>{}</h1></body></html>"#,
+        posting, CAREERS_PATH, title
+    ))
+}
+
+pub(crate) fn sitemap(state: &State) -> Response<Body> {
+    let urls = (0..state.config.jobs)
+        .map(|index| {
+            format!(
+                "<url><loc>{}{}{}</loc></url>",
+                state.url, POSTING_PATH, index
+            )
+        })
+        .collect::<String>();
+    response(
+        "application/xml; charset=utf-8",
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">{}</urlset>"#,
+            urls
+        ),
+    )
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use hyper::{Body, Response, StatusCode};
+
+use crate::{html, status, State};
+
+pub(crate) const SEARCH_PATH: &str = "/indeed/jobs";
+pub(crate) const POSTING_PATH: &str = "/indeed/viewjob";
+
+const PAGE_SIZE: u32 = 10;
+/// Searching in this location only returns the first postings, a subset of any other search
+const SMALL_LOCATION: &str = "Berlin";
+const SMALL_LOCATION_JOBS: u32 = 10;
+
+fn indeed_id(index: u32) -> String {
+    format!("{:016x}", 0xa1b2c3d4_u64 + index as u64)
+}
+
+fn card(index: u32) -> String {
+    format!(
+        r#"<div class="job_seen_beacon"><h2 class="jobTitle"><a data-jk="{}"><span title="Software Engineer {}">Software Engineer {}</span></a></h2>
+<span data-testid="company-name">Mock GmbH</span><div data-testid="text-location">Berlin</div></div>"#,
+        indeed_id(index),
+        index,
+        index
+    )
+}
+
+/// Offsets past the end repeat the last page, like the live site
+pub(crate) fn search(state: &State, params: &HashMap<String, String>) -> Response<Body> {
+    let total = match params.get("l").map(String::as_str) {
+        Some(SMALL_LOCATION) => state.config.jobs.min(SMALL_LOCATION_JOBS),
+        _ => state.config.jobs,
+    };
+    if total == 0 {
+        return html("<html><body></body></html>".to_owned());
+    }
+    let last_page = (total - 1) / PAGE_SIZE * PAGE_SIZE;
+    let start = params
+        .get("start")
+        .and_then(|start| start.parse::<u32>().ok())
+        .unwrap_or(0)
+        .min(last_page);
+    let cards = (start..total.min(start + PAGE_SIZE))
+        .map(card)
+        .collect::<String>();
+    html(format!("<html><body>{}</body></html>", cards))
+}
+
+pub(crate) fn posting(state: &State, params: &HashMap<String, String>) -> Response<Body> {
+    let index = match params
+        .get("jk")
+        .and_then(|id| (0..state.config.jobs).find(|index| indeed_id(*index) == *id))
+    {
+        Some(index) => index,
+        None => return status(StatusCode::NOT_FOUND),
+    };
+    html(format!(
+        r#"<html><body><h1 class="jobsearch-JobInfoHeader-title">Software Engineer {}</h1>
+<div id="jobDescriptionText"><p>We build services in Rust.</p></div></body></html>"#,
+        index
+    ))
+}
@@ -0,0 +1,130 @@
+use hyper::header::{COOKIE, SET_COOKIE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json::{json, Value};
+
+use std::sync::atomic::Ordering;
+
+use crate::{json, status, State};
+
+pub(crate) const SUGGESTIONS_PATH: &str = "/candidate/api/v1/job_suggestions";
+pub(crate) const LOGIN_PATH: &str = "/users/sign_in";
+
+const PAGE_SIZE: u32 = 20;
+const PIT_ID: &str = "mock-pit";
+
+const SESSION_COOKIE: &str = "_instaffo_session";
+/// Session cookie the suggestions are refused for with 401
+pub const EXPIRED_SESSION: &str = "expired";
+/// Every successful response renews the session to this value
+pub const RENEWED_SESSION: &str = "mock-session-renewed";
+/// Password the login accepts for any email
+pub const CANDIDATE_PASSWORD: &str = "mock-password";
+const LOGGED_IN_SESSION: &str = "mock-session-logged-in";
+
+fn with_session(mut resp: Response<Body>, session: &str) -> Response<Body> {
+    let cookie = format!("{}={}; path=/; HttpOnly", SESSION_COOKIE, session);
+    resp.headers_mut()
+        .insert(SET_COOKIE, cookie.parse().unwrap());
+    resp
+}
+
+/// Value of the session cookie sent with the request
+pub(crate) fn session(req: &Request<Body>) -> Option<String> {
+    req.headers()
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|cookies| cookies.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, value)| value.to_owned())
+}
+
+fn suggestion(index: u32) -> Value {
+    json!({
+        "favorite": index.is_multiple_of(5),
+        "seen": index.is_multiple_of(3),
+        "hidden": false,
+        "job": {
+            "uuid": format!("00000000-0000-4000-8000-{:012}", index),
+            "name": format!("Software Engineer {}", index),
+            "languages": [{"title": "English", "rating": "fluent", "must_have": true}],
+            "seniorities": ["professional"],
+            "management": false,
+            "degree": null,
+            "freelancer": false,
+            "willingnessToTravel": false,
+            "contractType": "permanent",
+            "remote": index.is_multiple_of(2),
+            "remoteType": null,
+            "salaryMin": 60000,
+            "salaryMax": 80000,
+            "currency": "EUR",
+            "company": {"name": "Mock Company", "companyType": "startup"},
+            "locations": [{
+                "uuid": "00000000-0000-4000-8000-000000000000",
+                "countryCode": "DE",
+                "country": "Germany",
+                "fullName": "Berlin, Germany",
+                "name": "Berlin",
+            }],
+            "topSkills": [{"uuid": "00000000-0000-4000-8000-000000000001", "name": "Rust"}],
+        },
+    })
+}
+
+/// The offset of the next page is carried in `searchAfter`, an empty page marks the end
+pub(crate) fn suggestions(state: &State, session: Option<&str>, body: &[u8]) -> Response<Body> {
+    if session.is_none_or(|session| session.is_empty() || session == EXPIRED_SESSION) {
+        return status(StatusCode::UNAUTHORIZED);
+    }
+    let count = state.suggestions.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Some(every) = state.config.expire_session_every {
+        if count.is_multiple_of(every) {
+            return status(StatusCode::UNAUTHORIZED);
+        }
+    }
+    let request: Value = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(_) => return status(StatusCode::BAD_REQUEST),
+    };
+    // points in time of an earlier search have expired
+    if request["pitId"]
+        .as_str()
+        .is_some_and(|pit_id| pit_id != PIT_ID)
+    {
+        return status(StatusCode::NOT_FOUND);
+    }
+    let offset = request["searchAfter"][0]
+        .as_str()
+        .and_then(|offset| offset.parse().ok())
+        .unwrap_or(0);
+    let total = state.config.jobs;
+    let end = total.min(offset + PAGE_SIZE);
+    let suggestions = (offset..end).map(suggestion).collect::<Vec<_>>();
+    let body = json!({
+        "jobSuggestions": suggestions,
+        "meta": {
+            "page": null,
+            "perPage": PAGE_SIZE,
+            "totalPages": total.div_ceil(PAGE_SIZE),
+            "totalResults": total,
+            "pitId": PIT_ID,
+            "searchAfter": [end.to_string(), format!("00000000-0000-4000-8000-{:012}", end)],
+        },
+    });
+    with_session(json(body.to_string()), RENEWED_SESSION)
+}
+
+/// Devise JSON sign in as the scraper assumes it, no CSRF token.
+/// Accepts `CANDIDATE_PASSWORD` for every account
+pub(crate) fn login(body: &[u8]) -> Response<Body> {
+    let request: Value = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(_) => return status(StatusCode::BAD_REQUEST),
+    };
+    if request["user"]["password"].as_str() != Some(CANDIDATE_PASSWORD) {
+        return status(StatusCode::UNAUTHORIZED);
+    }
+    with_session(json("{}".to_owned()), LOGGED_IN_SESSION)
+}
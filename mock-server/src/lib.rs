@@ -0,0 +1,278 @@
+mod arbeitsagentur;
+mod ats;
+mod careers;
+mod indeed;
+mod instaffo;
+mod linkedin;
+mod xing;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use hyper::header::{CONTENT_TYPE, RETRY_AFTER};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use tokio::sync::oneshot;
+
+pub use instaffo::{CANDIDATE_PASSWORD, EXPIRED_SESSION, RENEWED_SESSION};
+
+/// Behaviour of the mock job boards
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Number of postings every board serves
+    pub jobs: u32,
+    /// Every n-th request is answered with 429 and `Retry-After: 0`
+    pub rate_limit_every: Option<u32>,
+    /// Every n-th posting page has a body the scrapers can't parse
+    pub malformed_every: Option<u32>,
+    /// Served as `/robots.txt`, 404 if not set
+    pub robots_txt: Option<String>,
+    /// Every n-th instaffo suggestions request is answered with 401 as if the session expired
+    pub expire_session_every: Option<u32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            jobs: 50,
+            rate_limit_every: None,
+            malformed_every: None,
+            robots_txt: None,
+            expire_session_every: None,
+        }
+    }
+}
+
+struct State {
+    config: Config,
+    /// Base url the server is reachable under, used for links to postings
+    url: String,
+    requests: AtomicU32,
+    suggestions: AtomicU32,
+    /// Requests for the detail page of a xing, linkedin, indeed or arbeitsagentur posting
+    postings: AtomicU32,
+}
+
+impl State {
+    fn is_malformed(&self, index: u32) -> bool {
+        self.config
+            .malformed_every
+            .is_some_and(|every| (index + 1).is_multiple_of(every))
+    }
+}
+
+fn query_params(req: &Request<Body>) -> HashMap<String, String> {
+    req.uri()
+        .query()
+        .map(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn response(content_type: &str, body: impl Into<Body>) -> Response<Body> {
+    Response::builder()
+        .header(CONTENT_TYPE, content_type)
+        .body(body.into())
+        .unwrap()
+}
+
+fn html(body: String) -> Response<Body> {
+    response("text/html; charset=utf-8", body)
+}
+
+fn json(body: String) -> Response<Body> {
+    response("application/json; charset=utf-8", body)
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn handle(state: Arc<State>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let count = state.requests.fetch_add(1, Ordering::SeqCst) + 1;
+    log::debug!("{} {}", req.method(), req.uri());
+    if let Some(every) = state.config.rate_limit_every {
+        if count.is_multiple_of(every) {
+            let mut resp = status(StatusCode::TOO_MANY_REQUESTS);
+            resp.headers_mut().insert(RETRY_AFTER, "0".parse().unwrap());
+            return Ok(resp);
+        }
+    }
+    let path = req.uri().path().to_owned();
+    let resp = match (req.method(), path.as_str()) {
+        (&Method::GET, "/robots.txt") => match &state.config.robots_txt {
+            Some(robots_txt) => response("text/plain; charset=utf-8", robots_txt.clone()),
+            None => status(StatusCode::NOT_FOUND),
+        },
+        (&Method::GET, xing::SEARCH_PATH) => xing::search(&state, &query_params(&req)),
+        (&Method::GET, path) if path.starts_with(xing::POSTING_PATH) => {
+            state.postings.fetch_add(1, Ordering::SeqCst);
+            xing::posting(&state, &path[xing::POSTING_PATH.len()..])
+        }
+        (&Method::GET, linkedin::SEARCH_PATH) => linkedin::search(&state, &query_params(&req)),
+        (&Method::GET, path) if path.starts_with(linkedin::POSTING_PATH) => {
+            state.postings.fetch_add(1, Ordering::SeqCst);
+            linkedin::posting(&state, &path[linkedin::POSTING_PATH.len()..])
+        }
+        (&Method::GET, indeed::SEARCH_PATH) => indeed::search(&state, &query_params(&req)),
+        (&Method::GET, indeed::POSTING_PATH) => {
+            state.postings.fetch_add(1, Ordering::SeqCst);
+            indeed::posting(&state, &query_params(&req))
+        }
+        (&Method::GET, careers::CAREERS_PATH) => careers::careers(&state),
+        (&Method::GET, path) if path.starts_with(careers::POSTING_PATH) => {
+            careers::posting(&state, &path[careers::POSTING_PATH.len()..])
+        }
+        (&Method::GET, careers::SITEMAP_PATH) => careers::sitemap(&state),
+        (&Method::GET, path) if path.starts_with(ats::GREENHOUSE_PATH) => {
+            ats::greenhouse(&state, &path[ats::GREENHOUSE_PATH.len()..])
+        }
+        (&Method::GET, path) if path.starts_with(ats::LEVER_PATH) => {
+            ats::lever(&state, &path[ats::LEVER_PATH.len()..], &query_params(&req))
+        }
+        (&Method::GET, path) if path.starts_with(ats::PERSONIO_PATH) => {
+            ats::personio(&state, &path[ats::PERSONIO_PATH.len()..])
+        }
+        (&Method::GET, arbeitsagentur::SEARCH_PATH) => {
+            arbeitsagentur::search(&state, &req, &query_params(&req))
+        }
+        (&Method::GET, path) if path.starts_with(arbeitsagentur::DETAILS_PATH) => {
+            state.postings.fetch_add(1, Ordering::SeqCst);
+            arbeitsagentur::details(&state, &req, &path[arbeitsagentur::DETAILS_PATH.len()..])
+        }
+        (&Method::POST, instaffo::SUGGESTIONS_PATH) => {
+            let session = instaffo::session(&req);
+            match hyper::body::to_bytes(req.into_body()).await {
+                Ok(body) => instaffo::suggestions(&state, session.as_deref(), &body),
+                Err(_) => status(StatusCode::BAD_REQUEST),
+            }
+        }
+        (&Method::POST, instaffo::LOGIN_PATH) => match hyper::body::to_bytes(req.into_body()).await
+        {
+            Ok(body) => instaffo::login(&body),
+            Err(_) => status(StatusCode::BAD_REQUEST),
+        },
+        _ => status(StatusCode::NOT_FOUND),
+    };
+    Ok(resp)
+}
+
+/// Xing, LinkedIn, Instaffo, Indeed, the Jobbörse of the Arbeitsagentur, a company career site and
+/// the Greenhouse, Lever and Personio job boards served from a single local port.
+/// Point the scrapers at it with `XING_BASE_URL`, `LINKEDIN_BASE_URL`, `INSTAFFO_BASE_URL`
+/// and `INDEED_BASE_URL`,
+/// the server stops once this is dropped
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<State>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Starts the server on a random free port
+    pub async fn start(config: Config) -> Self {
+        Self::bind(([127, 0, 0, 1], 0).into(), config).await
+    }
+
+    pub async fn bind(addr: SocketAddr, config: Config) -> Self {
+        let listener = std::net::TcpListener::bind(addr).expect("Failed to bind mock server");
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(State {
+            config,
+            url: format!("http://{}", addr),
+            requests: AtomicU32::new(0),
+            suggestions: AtomicU32::new(0),
+            postings: AtomicU32::new(0),
+        });
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+        });
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let server = Server::from_tcp(listener)
+            .expect("Failed to start mock server")
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                stopped.await.ok();
+            });
+        tokio::spawn(server);
+        Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn xing_url(&self) -> String {
+        self.url()
+    }
+
+    pub fn linkedin_url(&self) -> String {
+        format!("{}/jobs-guest", self.url())
+    }
+
+    pub fn instaffo_url(&self) -> String {
+        self.url()
+    }
+
+    /// Career site with a JSON-LD `JobPosting` on every posting page
+    pub fn careers_url(&self) -> String {
+        format!("{}{}", self.url(), careers::CAREERS_PATH)
+    }
+
+    pub fn sitemap_url(&self) -> String {
+        format!("{}{}", self.url(), careers::SITEMAP_PATH)
+    }
+
+    pub fn indeed_url(&self) -> String {
+        format!("{}/indeed", self.url())
+    }
+
+    pub fn arbeitsagentur_url(&self) -> String {
+        format!("{}/arbeitsagentur", self.url())
+    }
+
+    pub fn greenhouse_url(&self) -> String {
+        format!("{}/greenhouse", self.url())
+    }
+
+    pub fn lever_url(&self) -> String {
+        format!("{}/lever", self.url())
+    }
+
+    /// Feeds are served for every company except "missing"
+    pub fn personio_url(&self) -> String {
+        format!("{}/personio/{{company}}", self.url())
+    }
+
+    /// Number of posting pages requested so far, rate limited requests are not counted
+    pub fn posting_requests(&self) -> u32 {
+        self.state.postings.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
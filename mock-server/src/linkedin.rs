@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use hyper::{Body, Response, StatusCode};
+
+use crate::{html, status, State};
+
+pub(crate) const SEARCH_PATH: &str = "/jobs-guest/jobs/api/seeMoreJobPostings/search";
+pub(crate) const POSTING_PATH: &str = "/jobs-guest/jobs/api/jobPosting/";
+
+/// The guest api always returns 25 cards per page
+const PAGE_SIZE: u32 = 25;
+const FIRST_ID: u64 = 3500000000;
+
+fn card(index: u32) -> String {
+    let id = FIRST_ID + index as u64;
+    format!(
+        r#"<li><div class="base-card base-search-card job-search-card" data-entity-urn="urn:li:jobPosting:{id}">
+<a class="base-card__full-link" href="https://de.linkedin.com/jobs/view/software-engineer-at-mock-company-{id}?refId=mock&amp;position={position}&amp;pageNum=0">
+<span class="sr-only">Software Engineer {index}</span></a></div></li>"#,
+        id = id,
+        position = index % PAGE_SIZE + 1,
+        index = index
+    )
+}
+
+/// Pages past the last posting are empty, like the live api
+pub(crate) fn search(state: &State, params: &HashMap<String, String>) -> Response<Body> {
+    let start = params
+        .get("start")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let cards = (start..state.config.jobs.min(start + PAGE_SIZE))
+        .map(card)
+        .collect::<String>();
+    html(cards)
+}
+
+pub(crate) fn posting(state: &State, id: &str) -> Response<Body> {
+    let index = match id
+        .parse::<u64>()
+        .ok()
+        .and_then(|id| id.checked_sub(FIRST_ID))
+        .filter(|index| *index < state.config.jobs as u64)
+    {
+        Some(index) => index as u32,
+        None => return status(StatusCode::NOT_FOUND),
+    };
+    if state.is_malformed(index) {
+        return html("<section><div class=\"top-card-layout".to_owned());
+    }
+    html(format!(
+        r#"<section class="top-card-layout">
+<h2 class="top-card-layout__title topcard__title">Software Engineer {index}</h2>
+<span class="topcard__flavor"><a class="topcard__org-name-link" href="https://de.linkedin.com/company/mock-company">Mock Company</a></span>
+<span class="topcard__flavor topcard__flavor--bullet">Berlin, Germany</span>
+<span class="posted-time-ago__text">{days} days ago</span>
+</section>
+<section class="description"><div class="description__text"><p>Build backend services in Rust.</p></div>
+<ul>
+<li><span class="description__job-criteria-text">Mid-Senior level</span></li>
+<li><span class="description__job-criteria-text">Full-time</span></li>
+<li><span class="description__job-criteria-text">Engineering</span></li>
+<li><span class="description__job-criteria-text">Software Development</span></li>
+</ul></section>"#,
+        index = index,
+        days = index % 6 + 1
+    ))
+}
@@ -0,0 +1,37 @@
+use std::net::SocketAddr;
+
+use mock_server::{Config, MockServer};
+
+fn env_u32(name: &str) -> Option<u32> {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+}
+
+/// Serves the mock job boards until interrupted, configured through
+/// `MOCK_ADDR`, `MOCK_JOBS`, `MOCK_RATE_LIMIT_EVERY`, `MOCK_MALFORMED_EVERY`,
+/// `MOCK_EXPIRE_SESSION_EVERY` and `MOCK_ROBOTS_TXT`, the path of a robots.txt to serve
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let addr: SocketAddr = std::env::var("MOCK_ADDR")
+        .unwrap_or_else(|_| "127.0.0.1:8900".to_owned())
+        .parse()
+        .expect("MOCK_ADDR must be a socket address");
+    let config = Config {
+        jobs: env_u32("MOCK_JOBS").unwrap_or(Config::default().jobs),
+        rate_limit_every: env_u32("MOCK_RATE_LIMIT_EVERY"),
+        malformed_every: env_u32("MOCK_MALFORMED_EVERY"),
+        robots_txt: std::env::var("MOCK_ROBOTS_TXT")
+            .ok()
+            .map(|path| std::fs::read_to_string(path).expect("MOCK_ROBOTS_TXT must be readable")),
+        expire_session_every: env_u32("MOCK_EXPIRE_SESSION_EVERY"),
+    };
+    let server = MockServer::bind(addr, config).await;
+    println!("XING_BASE_URL={}", server.xing_url());
+    println!("LINKEDIN_BASE_URL={}", server.linkedin_url());
+    println!("INSTAFFO_BASE_URL={}", server.instaffo_url());
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Failed to listen for ctrl-c");
+}
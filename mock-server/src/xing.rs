@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use hyper::{Body, Response, StatusCode};
+use serde_json::json;
+
+use crate::{html, json, status, State};
+
+pub(crate) const SEARCH_PATH: &str = "/jobs/api/search";
+pub(crate) const POSTING_PATH: &str = "/jobs/";
+
+/// Searching for this keyword returns a truncated json body
+const MALFORMED_KEYWORD: &str = "malformed";
+
+const FIRST_ID: u32 = 90000000;
+
+fn slug(id: u32) -> String {
+    format!("berlin-software-engineer-{}", id)
+}
+
+fn item(state: &State, index: u32) -> serde_json::Value {
+    let id = FIRST_ID + index;
+    json!({
+        "id": id,
+        "scrambledId": format!("{}.mock", id),
+        "company": {
+            "name": format!("Mock Company {}", index % 7),
+            "link": null,
+            "kununuData": null,
+        },
+        "favoritePosting": null,
+        "highlight": null,
+        "isBookmarked": false,
+        "isProjob": false,
+        "link": format!("{}{}{}", state.url, POSTING_PATH, slug(id)),
+        "location": "Berlin",
+        "position": index + 1,
+        "thumbnail": null,
+        "activatedAt": "2023-05-02T08:00:00Z",
+        "path": format!("{}{}", POSTING_PATH, slug(id)),
+        "slug": slug(id),
+        "title": format!("Software Engineer {}", index),
+        "trackingToken": null,
+    })
+}
+
+pub(crate) fn search(state: &State, params: &HashMap<String, String>) -> Response<Body> {
+    let keywords = params.get("keywords").map(String::as_str).unwrap_or("");
+    if keywords == MALFORMED_KEYWORD {
+        return json(r#"{"items":[{"id":1,"#.to_owned());
+    }
+    let offset = params
+        .get("offset")
+        .and_then(|o| o.parse().ok())
+        .unwrap_or(0);
+    let limit: u32 = params
+        .get("limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(20)
+        .max(1);
+    let total = state.config.jobs;
+    let items = (offset..total.min(offset + limit))
+        .map(|index| item(state, index))
+        .collect::<Vec<_>>();
+    let body = json!({
+        "items": items,
+        "meta": {
+            "count": total,
+            "currentPage": offset / limit + 1,
+            "maxPage": total.div_ceil(limit),
+        },
+    });
+    json(body.to_string())
+}
+
+pub(crate) fn posting(state: &State, slug: &str) -> Response<Body> {
+    let index = match slug
+        .rsplit('-')
+        .next()
+        .and_then(|id| id.parse::<u32>().ok())
+        .and_then(|id| id.checked_sub(FIRST_ID))
+        .filter(|index| *index < state.config.jobs)
+    {
+        Some(index) => index,
+        None => return status(StatusCode::NOT_FOUND),
+    };
+    if state.is_malformed(index) {
+        return html("<html><body><h1>Something went wrong".to_owned());
+    }
+    html(format!(
+        r#"<!DOCTYPE html><html lang="de"><body><main>
+<div class="styles-grid-gridContainer-cec162b7 styles-grid-standardGridContainer-cfa898d5">
+<h1>Software Engineer {index}</h1><p>Mock Company {company}</p>
+<p>Du entwickelst Backend Services in Rust und Go.</p></div>
+<div data-cy="posting-salary">€60,000 – €80,000</div>
+</main></body></html>"#,
+        index = index,
+        company = index % 7
+    ))
+}
@@ -1,9 +1,18 @@
 use ai_analyzer::types::JobDetails;
 use async_trait::async_trait;
-use mongodb::{bson::oid::ObjectId, results::InsertManyResult};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use job_scraper::checkpoint::CheckpointStore;
+use job_scraper::stats::Counts;
+use mongodb::{
+    bson::{doc, oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime, Bson},
+    error::ErrorKind,
+    options::{FindOptions, ReplaceOptions},
+    results::InsertManyResult,
+};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
     hash::{Hash, Hasher},
 };
 
@@ -42,6 +51,8 @@ pub struct Job {
 }
 
 pub const COLLECTION_JOBS: &str = "analyzed-jobs";
+pub const COLLECTION_CHECKPOINTS: &str = "scrape-checkpoints";
+pub const COLLECTION_RUNS: &str = "scrape-runs";
 
 pub async fn connect(mongodb_connection_url: &str, database_name: &str) -> mongodb::Database {
     let client = mongodb::Client::with_uri_str(mongodb_connection_url)
@@ -64,7 +75,227 @@ where
     col.insert_many(docs, options).await
 }
 
+const DUPLICATE_KEY: i32 = 11000;
+
+/// Number of documents an unordered insert skipped because they were stored before,
+/// `None` if anything else went wrong
+pub fn duplicates(error: &mongodb::error::Error) -> Option<u32> {
+    let ErrorKind::BulkWrite(failure) = error.kind.as_ref() else {
+        return None;
+    };
+    let write_errors = failure.write_errors.as_ref()?;
+    if failure.write_concern_error.is_some() || write_errors.iter().any(|e| e.code != DUPLICATE_KEY)
+    {
+        return None;
+    }
+    Some(write_errors.len() as u32)
+}
+
+/// A single `cli scrape` of one site and how it went
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScrapeRun {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub site: String,
+    pub queries: Vec<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub started_at: DateTime<Utc>,
+    /// Same as `started_at` until the run is finished
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub finished_at: DateTime<Utc>,
+    /// Successful responses, search results and posting pages alike
+    pub pages: u32,
+    /// Postings the source returned, including the ones already stored
+    pub postings: u32,
+    pub inserted: u32,
+    pub duplicates: u32,
+    /// Error responses by status code
+    pub http_errors: BTreeMap<String, u32>,
+    pub extraction_failures: u32,
+}
+
+impl ScrapeRun {
+    pub fn start(site: &str, queries: Vec<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: None,
+            site: site.to_owned(),
+            queries,
+            started_at: now,
+            finished_at: now,
+            pages: 0,
+            postings: 0,
+            inserted: 0,
+            duplicates: 0,
+            http_errors: BTreeMap::new(),
+            extraction_failures: 0,
+        }
+    }
+
+    /// Takes over the counts of the scrapers' clients, see `job_scraper::stats`
+    pub fn finish(&mut self, counts: Counts) {
+        self.finished_at = Utc::now();
+        self.pages = counts.pages;
+        self.extraction_failures = counts.extraction_failures;
+        self.http_errors = counts
+            .http_errors
+            .into_iter()
+            .map(|(status, count)| (status.to_string(), count))
+            .collect();
+    }
+}
+
+pub async fn save_run(
+    db: &mongodb::Database,
+    run: &ScrapeRun,
+) -> Result<(), mongodb::error::Error> {
+    db.collection::<ScrapeRun>(COLLECTION_RUNS)
+        .insert_one(run, None)
+        .await?;
+    Ok(())
+}
+
+/// The last `limit` runs, newest first
+pub async fn recent_runs(
+    db: &mongodb::Database,
+    site: Option<&str>,
+    limit: i64,
+) -> Result<Vec<ScrapeRun>, mongodb::error::Error> {
+    let filter = site.map(|site| doc! {"site": site.to_lowercase()});
+    let options = FindOptions::builder()
+        .sort(doc! {"started_at": -1})
+        .limit(limit)
+        .build();
+    db.collection::<ScrapeRun>(COLLECTION_RUNS)
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await
+}
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    /// `{source}:{search}`, a source keeps a single cursor per search
+    #[serde(rename = "_id")]
+    id: String,
+    source: String,
+    search: String,
+    cursor: serde_json::Value,
+}
+
+/// Cursors of interrupted scrapes in the `scrape-checkpoints` collection
+pub struct MongoCheckpoints {
+    col: mongodb::Collection<Checkpoint>,
+}
+
+impl MongoCheckpoints {
+    pub fn new(db: &mongodb::Database) -> Self {
+        Self {
+            col: db.collection(COLLECTION_CHECKPOINTS),
+        }
+    }
+}
+
+fn checkpoint_id(source: &str, search: &str) -> String {
+    format!("{}:{}", source, search)
+}
+
+#[async_trait]
+impl CheckpointStore for MongoCheckpoints {
+    async fn load(&self, source: &str, search: &str) -> Option<serde_json::Value> {
+        let filter = doc! {"_id": checkpoint_id(source, search)};
+        match self.col.find_one(filter, None).await {
+            Ok(checkpoint) => checkpoint.map(|checkpoint| checkpoint.cursor),
+            Err(e) => {
+                log::error!("Failed to load the checkpoint of {}: {}", source, e);
+                None
+            }
+        }
+    }
+
+    async fn save(&self, source: &str, search: &str, cursor: serde_json::Value) {
+        let id = checkpoint_id(source, search);
+        let checkpoint = Checkpoint {
+            id: id.clone(),
+            source: source.to_owned(),
+            search: search.to_owned(),
+            cursor,
+        };
+        let options = ReplaceOptions::builder().upsert(true).build();
+        if let Err(e) = self
+            .col
+            .replace_one(doc! {"_id": id}, checkpoint, options)
+            .await
+        {
+            log::error!("Failed to save the checkpoint of {}: {}", source, e);
+        }
+    }
+
+    async fn remove(&self, source: &str, search: &str) {
+        let filter = doc! {"_id": checkpoint_id(source, search)};
+        if let Err(e) = self.col.delete_one(filter, None).await {
+            log::error!("Failed to remove the checkpoint of {}: {}", source, e);
+        }
+    }
+}
+
+/// Type tag and field of the site id of a stored posting, see `job_scraper::Job::site_id`
+fn site_id_field(site: &str) -> Option<(&'static str, &'static str)> {
+    match site.to_lowercase().as_str() {
+        "xing" => Some(("Xing", "job.job.id")),
+        "linkedin" => Some(("Linkedin", "job.job.linkedin_id")),
+        "instaffo" => Some(("Instaffo", "job.job.job.uuid")),
+        "stepstone" => Some(("Stepstone", "job.job.stepstone_id")),
+        "glassdoor" => Some(("Glassdoor", "job.job.glassdoor_id")),
+        "indeed" => Some(("Indeed", "job.job.indeed_id")),
+        "jsonld" => Some(("Generic", "job.job.url")),
+        "greenhouse" => Some(("Greenhouse", "job.job.greenhouse_id")),
+        "lever" => Some(("Lever", "job.job.lever_id")),
+        "personio" => Some(("Personio", "job.job.personio_id")),
+        "arbeitsagentur" => Some(("Arbeitsagentur", "job.job.refnr")),
+        _ => None,
+    }
+}
+
+/// Site ids of every posting of `site` in the collection, the scrapers skip these
+/// so a run only fetches postings that are new
+pub async fn known_site_ids(
+    col: &mongodb::Collection<ScrapedJob>,
+    site: &str,
+) -> Result<HashSet<String>, mongodb::error::Error> {
+    let Some((job_type, field)) = site_id_field(site) else {
+        log::warn!(
+            "No site id known for {}, scraping all of its postings",
+            site
+        );
+        return Ok(HashSet::new());
+    };
+    let ids = col
+        .distinct(field, doc! {"job.type": job_type}, None)
+        .await?
+        .into_iter()
+        .filter_map(|id| match id {
+            Bson::String(id) => Some(id),
+            Bson::Int32(id) => Some(id.to_string()),
+            Bson::Int64(id) => Some(id.to_string()),
+            _ => None,
+        })
+        .collect();
+    Ok(ids)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_every_source_has_a_site_id_field() {
+        for source in job_scraper::source::registry() {
+            assert!(
+                site_id_field(source.name()).is_some(),
+                "No site id field for {}",
+                source.name()
+            );
+        }
+    }
 }